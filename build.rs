@@ -6,6 +6,11 @@ fn main() {
     cbindgen::Builder::new()
       .with_crate(&crate_dir)
       .with_language(cbindgen::Language::Cxx)
+      // Most functions take these as `int32_t`, so not all of them are reached from a signature
+      .include_item("Direction")
+      .include_item("CellColor")
+      .include_item("StatQuery")
+      .include_item("Assumption")
       .generate()
       .expect("Unable to generate bindings")
       .write_to_file("./wrappers/cxx/bindings.hpp");
@@ -14,6 +19,11 @@ fn main() {
     cbindgen::Builder::new()
       .with_crate(&crate_dir)
       .with_language(cbindgen::Language::C)
+      // Most functions take these as `int32_t`, so not all of them are reached from a signature
+      .include_item("Direction")
+      .include_item("CellColor")
+      .include_item("StatQuery")
+      .include_item("Assumption")
       .generate()
       .expect("Unable to generate bindings")
       .write_to_file("./wrappers/c/bindings.h");
@@ -21,8 +31,13 @@ fn main() {
     #[cfg(feature = "dotnet")]
     csbindgen::Builder::default()
         .input_extern_file("src/c_api.rs")
+        .input_extern_file("src/lib.rs")
+        .input_extern_file("src/maze.rs")
+        .input_extern_file("src/solver.rs")
         .csharp_namespace("mms_sharp")
         .csharp_class_name("MmsApi")
+        // Mouse.cs uses the generated enums in its public api
+        .csharp_class_accessibility("public")
         .csharp_dll_name("MmsSharp")
         .generate_csharp_file("./wrappers/dotnet/MmsApi.cs")
         .unwrap();
@@ -1,16 +1,18 @@
 use mms_rs::MmsApi as Mouse;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+// Under `use_panics` every command is `must_use`, even the ones returning `()`.
+#[allow(unused_must_use)]
+fn main() {
     eprintln!("Running...");
-    Mouse::set_color(0, 0, &mms_rs::CellColor::DarkGreen)?;
-    Mouse::set_text(0, 0, "abc")?;
+    Mouse::set_color(0, 0, &mms_rs::CellColor::DarkGreen);
+    Mouse::set_text(0, 0, "abc");
     loop {
-        if !Mouse::wall_left()? {
-            Mouse::turn_left()?;
+        if !Mouse::wall_left() {
+            Mouse::turn_left();
         }
-        while Mouse::wall_front()? {
-            Mouse::turn_right()?;
+        while Mouse::wall_front() {
+            Mouse::turn_right();
         }
-        Mouse::move_forward(None)?;
+        Mouse::move_forward(None);
     }
 }
@@ -0,0 +1,147 @@
+//! Bridges mms to a bot that speaks JSON lines, so bots can be written in any language.
+//!
+//! Use one of these as the run command in mms:
+//! - `mms-bridge <program> [args...]` starts the bot and talks to it over its `stdin` and
+//!   `stdout`. Its `stderr` goes to mms.
+//! - `mms-bridge --listen <address>` waits for the bot to connect over TCP.
+//! - `mms-bridge --connect <address>` connects to a bot listening over TCP.
+//!
+//! The bot sends one [`Command`] per line, e.g. `{"cmd":"setColor","x":0,"y":0,"color":"Red"}`,
+//! and gets exactly one line back: `{"ok":true}`, `{"ok":true,"value":16}` or
+//! `{"ok":false,"error":{"kind":"InvalidAck","message":"..."}}`. Stats without a value yet are
+//! `null`, and a line that is not a valid command fails with the kind `InvalidRequest`.
+
+use std::{
+    env,
+    io::{self, BufReader},
+    net::{TcpListener, TcpStream},
+    process::{self, ExitCode},
+};
+
+use mms_rs::{
+    connection::{Connection, LineTransport, Stdio, Streams},
+    protocol::{Command, Response},
+    MmsError,
+};
+use serde_json::{json, Value};
+
+const USAGE: &str = "usage: mms-bridge <program> [args...]
+       mms-bridge --listen <address>
+       mms-bridge --connect <address>";
+
+fn response_json(response: Response) -> Value {
+    match response {
+        Response::None | Response::Ack => json!({ "ok": true }),
+        Response::Size(size) => json!({ "ok": true, "value": size }),
+        Response::Bool(value) => json!({ "ok": true, "value": value }),
+        Response::Stat(stat) => {
+            let value = stat
+                .int_value()
+                .map_or_else(|| json!(stat.float_value()), Value::from);
+            json!({ "ok": true, "value": value })
+        }
+    }
+}
+
+fn error_json(kind: &str, message: &str) -> Value {
+    json!({ "ok": false, "error": { "kind": kind, "message": message } })
+}
+
+/// The error without the raw text of its `Display`, which repeats the kind and keeps line breaks
+fn mms_error_json(error: &MmsError) -> Value {
+    let (kind, message) = match error {
+        MmsError::ParseIntError(error) => ("ParseIntError", error.to_string()),
+        MmsError::ParseFloatError(error) => ("ParseFloatError", error.to_string()),
+        MmsError::ParseStatQueryError(query) => {
+            ("ParseStatQueryError", format!("unknown stat {query:?}"))
+        }
+        MmsError::IoError(error) => ("IoError", error.to_string()),
+        MmsError::InvalidAck(response) => (
+            "InvalidAck",
+            format!("expected \"ack\", mms answered {:?}", response.trim_end()),
+        ),
+        MmsError::InvalidColorString(color) => {
+            ("InvalidColorString", format!("unknown color {color:?}"))
+        }
+        MmsError::InvalidDirectionString(direction) => (
+            "InvalidDirectionString",
+            format!("unknown direction {direction:?}"),
+        ),
+        MmsError::InvalidMazeFile(message) => ("InvalidMazeFile", message.clone()),
+        MmsError::InvalidMazeSize(message) => ("InvalidMazeSize", message.clone()),
+    };
+    error_json(kind, &message)
+}
+
+fn execute(mms: &mut Connection<Stdio>, command: &Command) -> Value {
+    match mms.execute(command) {
+        Ok(response) => response_json(response),
+        Err(error) => mms_error_json(&error),
+    }
+}
+
+/// Answers every command of the bot until it closes the connection
+fn bridge(bot: &mut impl LineTransport) -> io::Result<()> {
+    let mut mms = Connection::new(Stdio);
+    loop {
+        let line = bot.read_line()?;
+        if line.is_empty() {
+            return Ok(());
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Command>(line) {
+            Ok(command) => execute(&mut mms, &command),
+            Err(error) => error_json("InvalidRequest", &error.to_string()),
+        };
+        bot.write_line(&reply.to_string())?;
+    }
+}
+
+fn serve(stream: TcpStream) -> io::Result<()> {
+    let reader = BufReader::new(stream.try_clone()?);
+    bridge(&mut Streams::new(reader, stream))
+}
+
+fn run_child(program: &str, args: &[String]) -> io::Result<()> {
+    let mut child = process::Command::new(program)
+        .args(args)
+        .stdin(process::Stdio::piped())
+        .stdout(process::Stdio::piped())
+        .spawn()?;
+    let stdin = child.stdin.take().expect("stdin is piped");
+    let stdout = child.stdout.take().expect("stdout is piped");
+    // dropping the streams closes the bot's stdin, so it can exit
+    let result = bridge(&mut Streams::new(BufReader::new(stdout), stdin));
+    let status = child.wait()?;
+    result?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{program} exited with {status}")))
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.as_slice() {
+        [flag, address] if flag == "--listen" => TcpListener::bind(address)
+            .and_then(|listener| listener.accept())
+            .and_then(|(stream, _)| serve(stream)),
+        [flag, address] if flag == "--connect" => TcpStream::connect(address).and_then(serve),
+        [program, args @ ..] if !program.starts_with("--") => run_child(program, args),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("mms-bridge: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Runs a bot script, see [`mms_rs::script`]. Use `mms-script path/to/bot.rhai` as the run
+//! command in mms.
+
+use std::{env, path::PathBuf, process::ExitCode};
+
+fn main() -> ExitCode {
+    let Some(path) = env::args_os().nth(1).map(PathBuf::from) else {
+        eprintln!("usage: mms-script <script>");
+        return ExitCode::FAILURE;
+    };
+    match mms_rs::script::run_file(&path) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("{}: {error}", path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,332 @@
+//! A reset-aware event loop that drives a [`MouseController`] step by step
+
+use std::num::NonZeroU32;
+
+use crate::{
+    canvas::Canvas,
+    consistency::{self, ConflictPolicy, Contradiction},
+    explore::Explorer,
+    maze::{Cell, Maze, Pose},
+    solver::path_directions,
+    store::MazeStore,
+    Direction, MmsApi, MmsError, ResultType,
+};
+
+/// What the mouse should do next
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Move forward the given number of cells
+    MoveForward(NonZeroU32),
+    /// Turn ninety degrees to the left
+    TurnLeft,
+    /// Turn ninety degrees to the right
+    TurnRight,
+    /// End the bot loop
+    Stop,
+}
+
+impl Action {
+    /// The next action on the way to leaving the current cell in the given direction. Turning
+    /// around takes two steps.
+    #[must_use]
+    pub fn towards(pose: &Pose, direction: Direction) -> Self {
+        if direction == pose.heading {
+            Action::MoveForward(NonZeroU32::MIN)
+        } else if direction == pose.heading.left() {
+            Action::TurnLeft
+        } else {
+            Action::TurnRight
+        }
+    }
+}
+
+/// Turns the path into the actions that drive it from the given pose. Straight segments become
+/// a single multi-cell `MoveForward`, which mms counts with a lower effective distance.
+#[must_use]
+pub fn path_actions(pose: &Pose, path: &[Cell]) -> Vec<Action> {
+    let mut actions = Vec::new();
+    let mut heading = pose.heading;
+    let mut straight = 0;
+    for direction in path_directions(path) {
+        if direction != heading && straight > 0 {
+            actions.extend(NonZeroU32::new(straight).map(Action::MoveForward));
+            straight = 0;
+        }
+        if direction == heading.left() {
+            actions.push(Action::TurnLeft);
+        } else if direction == heading.right() {
+            actions.push(Action::TurnRight);
+        } else if direction == heading.opposite() {
+            actions.extend([Action::TurnRight, Action::TurnRight]);
+        }
+        heading = direction;
+        straight += 1;
+    }
+    actions.extend(NonZeroU32::new(straight).map(Action::MoveForward));
+    actions
+}
+
+/// Everything the bot loop knows about the maze and the mouse
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BotState {
+    pub maze: Maze,
+    /// Only the walls sensed by this process, without knowledge restored from a [`MazeStore`]
+    pub observed: Maze,
+    pub pose: Pose,
+    /// Every sensor reading so far that contradicted a known wall
+    pub contradictions: Vec<Contradiction>,
+}
+
+impl BotState {
+    /// Creates the state for a mouse standing on the start cell of the given maze
+    #[must_use]
+    pub fn new(maze: Maze) -> Self {
+        Self {
+            observed: Maze::new(maze.width(), maze.height()),
+            maze,
+            pose: Pose::start(),
+            contradictions: Vec::new(),
+        }
+    }
+
+    /// Reads the three wall sensors and records them at the current pose. Readings that
+    /// contradict known walls are marked red through the canvas and returned; the policy decides
+    /// which side is kept.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn sense(
+        &mut self,
+        canvas: &mut Canvas,
+        policy: ConflictPolicy,
+    ) -> ResultType<Vec<Contradiction>> {
+        let front = try_api!(MmsApi::wall_front());
+        let left = try_api!(MmsApi::wall_left());
+        let right = try_api!(MmsApi::wall_right());
+        let contradictions =
+            consistency::check_sensors(&mut self.maze, &self.pose, front, left, right, policy);
+        self.observed.record_sensors(&self.pose, front, left, right);
+        try_api!(consistency::mark(canvas, &contradictions));
+        self.contradictions.extend_from_slice(&contradictions);
+        return_result!(contradictions);
+    }
+
+    /// Sends the action to mms and tracks its effect on the pose
+    ///
+    /// # Errors
+    /// `IoError`
+    /// `InvalidAck`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn perform(&mut self, action: Action) -> ResultType<()> {
+        match action {
+            Action::MoveForward(distance) => {
+                try_api!(MmsApi::move_forward(Some(distance)));
+                self.pose.move_forward(distance.get());
+            }
+            Action::TurnLeft => {
+                try_api!(MmsApi::turn_left());
+                self.pose.turn_left();
+            }
+            Action::TurnRight => {
+                try_api!(MmsApi::turn_right());
+                self.pose.turn_right();
+            }
+            Action::Stop => {}
+        }
+        return_result!(());
+    }
+
+    /// Puts the mouse back on the start cell, optionally forgetting everything about the maze
+    pub fn reset(&mut self, keep_maze: bool) {
+        self.pose = Pose::start();
+        if !keep_maze {
+            self.maze = Maze::new(self.maze.width(), self.maze.height());
+            self.observed = self.maze.clone();
+        }
+    }
+}
+
+/// The decision making part of a bot. The [`run_bot`] loop takes care of sensing, moving and
+/// handling resets.
+pub trait MouseController {
+    /// Decides on the next action. Called once per step, after the walls around the current pose
+    /// were recorded in the maze.
+    ///
+    /// # Errors
+    /// Any error returned here ends the bot loop
+    fn step(&mut self, state: &BotState) -> ResultType<Action>;
+
+    /// Called after every step to draw debugging visuals. Drawing through the canvas keeps it in
+    /// sync with the contradiction markers of the loop.
+    ///
+    /// # Errors
+    /// Any error returned here ends the bot loop
+    #[allow(unused_variables)]
+    fn draw(&mut self, state: &BotState, canvas: &mut Canvas) -> ResultType<()> {
+        return_result!(());
+    }
+
+    /// Called for every sensor reading that contradicts a known wall, after the
+    /// [`ConflictPolicy`] decided which side is kept. The contradictions are also collected in
+    /// [`BotState::contradictions`].
+    #[allow(unused_variables)]
+    fn contradiction(&mut self, contradiction: &Contradiction) {}
+
+    /// Called when the maze restored from the [`MazeStore`] disagrees with a later reading and
+    /// is dropped, leaving only the walls sensed by this process
+    fn restore_dropped(&mut self) {}
+
+    /// Called after a reset was acknowledged and the pose was moved back to the start. Should
+    /// put the controller back into its initial phase.
+    fn reset(&mut self) {}
+}
+
+impl MouseController for Explorer {
+    fn step(&mut self, state: &BotState) -> ResultType<Action> {
+        let action = self
+            .next_move(&state.maze, &state.pose)
+            .map_or(Action::Stop, |direction| {
+                Action::towards(&state.pose, direction)
+            });
+        return_result!(action);
+    }
+
+    fn reset(&mut self) {
+        Explorer::reset(self);
+    }
+}
+
+/// Options for [`run_bot`]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RunOptions {
+    /// Keep the walls learned so far when the reset button is pressed. Default = `true`
+    pub keep_maze_on_reset: bool,
+    /// Where to save the maze and look for previous exploration of the same maze.
+    /// Default = `None`
+    pub store: Option<MazeStore>,
+    /// Whether readings that contradict known walls replace them. Default = `TrustNew`
+    pub conflict_policy: ConflictPolicy,
+    /// How many walls inside the maze must be sensed before a saved maze is restored. Right
+    /// after the start the few readings agree with nearly every saved maze. Default = `12`
+    pub walls_before_restore: usize,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        Self {
+            keep_maze_on_reset: true,
+            store: None,
+            conflict_policy: ConflictPolicy::default(),
+            walls_before_restore: 12,
+        }
+    }
+}
+
+/// An empty maze of the size reported by mms
+fn maze_of_size(width: i32, height: i32) -> Result<Maze, MmsError> {
+    match (u32::try_from(width), u32::try_from(height)) {
+        (Ok(width @ 1..), Ok(height @ 1..)) => Ok(Maze::new(width, height)),
+        _ => Err(MmsError::InvalidMazeSize(format!(
+            "mms reported a {width}x{height} maze"
+        ))),
+    }
+}
+
+/// Runs the controller until it returns [`Action::Stop`] and returns the final state.
+///
+/// Before every step the loop checks `was_reset`. When the reset button was pressed it calls
+/// `ack_reset`, moves the pose back to the start and resets the controller. Sensor readings that
+/// contradict known walls are passed to [`MouseController::contradiction`].
+/// After every step the controller can draw through the [`Canvas`] of the loop, see
+/// [`MouseController::draw`].
+///
+/// With a [`MazeStore`] the loop looks for a saved maze that agrees with the sensor readings once
+/// [`walls_before_restore`](RunOptions::walls_before_restore) walls were sensed, and merges it
+/// into the known maze. Every later reading is checked against the restored maze, which is
+/// dropped again on the first disagreement, see [`MouseController::restore_dropped`]. The maze is saved every time a new cell is visited
+/// and when the loop ends.
+///
+/// # Errors
+/// `IoError`
+/// `ParseIntError`
+/// `InvalidAck`
+/// `InvalidMazeSize` if mms reports a width or height below `1`
+/// and any error returned by the controller
+/// # Panics
+/// this panics when `use_panics` is disabled
+pub fn run_bot<C: MouseController + ?Sized>(
+    controller: &mut C,
+    options: &RunOptions,
+) -> ResultType<BotState> {
+    let width = try_api!(MmsApi::maze_width());
+    let height = try_api!(MmsApi::maze_height());
+    let mut state = BotState::new(handle_result!(maze_of_size(width, height)));
+    let mut canvas = Canvas::new(state.maze.width(), state.maze.height());
+    let mut restored: Option<Maze> = None;
+    loop {
+        if try_api!(MmsApi::was_reset()) {
+            try_api!(MmsApi::ack_reset());
+            state.reset(options.keep_maze_on_reset);
+            if !options.keep_maze_on_reset {
+                restored = None;
+            }
+            controller.reset();
+        }
+        let new_cell = !state.maze.is_visited(state.pose.cell);
+        for contradiction in try_api!(state.sense(&mut canvas, options.conflict_policy)) {
+            controller.contradiction(&contradiction);
+        }
+        if let Some(store) = &options.store {
+            match &restored {
+                Some(saved) if !saved.agrees_with(&state.observed) => {
+                    restored = None;
+                    state.maze = state.observed.clone();
+                    controller.restore_dropped();
+                }
+                None if new_cell
+                    && state.observed.sensed_walls() >= options.walls_before_restore =>
+                {
+                    if let Some(saved) = handle_result!(store.find(&state.observed)) {
+                        let mut known = saved.clone();
+                        known.merge(&state.maze);
+                        state.maze = known;
+                        restored = Some(saved);
+                    }
+                }
+                _ => {}
+            }
+            if new_cell {
+                handle_result!(store.save(&state.maze));
+            }
+        }
+        let action = try_api!(controller.step(&state));
+        try_api!(controller.draw(&state, &mut canvas));
+        match action {
+            Action::Stop => break,
+            action => try_api!(state.perform(action)),
+        }
+    }
+    if let Some(store) = &options.store {
+        handle_result!(store.save(&state.maze));
+    }
+    return_result!(state);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maze_sizes_below_one_are_rejected() {
+        assert_eq!(maze_of_size(16, 8).unwrap(), Maze::new(16, 8));
+        for (width, height) in [(0, 16), (16, 0), (-1, 16), (16, -5)] {
+            assert!(matches!(
+                maze_of_size(width, height),
+                Err(MmsError::InvalidMazeSize(_))
+            ));
+        }
+    }
+}
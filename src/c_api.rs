@@ -1,6 +1,139 @@
-use std::num::NonZeroU32;
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::{c_char, c_void, CStr},
+    num::NonZeroU32,
+    panic::{self, AssertUnwindSafe},
+};
 
-use crate::{MmsApi as Api, Stat};
+use crate::{
+    connection::{Connection, LineTransport, Stdio},
+    grid::Grid,
+    maze::{self, Cell, Maze, WallState},
+    solver::{self, Assumption},
+    stats, CellColor, Direction, MmsError, Stat, StatQuery,
+};
+
+/// The result of every function of the C api. Everything except `Ok` means the call failed and
+/// `mms_last_error_message` describes why.
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MmsStatus {
+    Ok = 0,
+    ParseIntError,
+    ParseFloatError,
+    ParseStatQueryError,
+    IoError,
+    InvalidAck,
+    InvalidColorString,
+    InvalidDirectionString,
+    InvalidMazeFile,
+    /// An argument was invalid, e.g. a null pointer, a negative length or an enum value out of
+    /// range
+    InvalidArgument,
+    /// The call panicked. The panic was caught before it could unwind into the caller.
+    Panic,
+    InvalidMazeSize,
+}
+
+impl From<&MmsError> for MmsStatus {
+    fn from(error: &MmsError) -> Self {
+        match error {
+            MmsError::ParseIntError(_) => MmsStatus::ParseIntError,
+            MmsError::ParseFloatError(_) => MmsStatus::ParseFloatError,
+            MmsError::ParseStatQueryError(_) => MmsStatus::ParseStatQueryError,
+            MmsError::IoError(_) => MmsStatus::IoError,
+            MmsError::InvalidAck(_) => MmsStatus::InvalidAck,
+            MmsError::InvalidColorString(_) => MmsStatus::InvalidColorString,
+            MmsError::InvalidDirectionString(_) => MmsStatus::InvalidDirectionString,
+            MmsError::InvalidMazeFile(_) => MmsStatus::InvalidMazeFile,
+            MmsError::InvalidMazeSize(_) => MmsStatus::InvalidMazeSize,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        format!("Panic: {message}")
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        format!("Panic: {message}")
+    } else {
+        "Panic".to_string()
+    }
+}
+
+/// Runs `f`, catching panics, and turns errors into a status while remembering their message
+fn call<T>(f: impl FnOnce() -> Result<T, MmsError>) -> Result<T, MmsStatus> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(error)) => {
+            set_last_error(error.to_string());
+            Err(MmsStatus::from(&error))
+        }
+        Err(payload) => {
+            set_last_error(panic_message(payload.as_ref()));
+            Err(MmsStatus::Panic)
+        }
+    }
+}
+
+fn call_status(f: impl FnOnce() -> Result<(), MmsError>) -> MmsStatus {
+    match call(f) {
+        Ok(()) => MmsStatus::Ok,
+        Err(status) => status,
+    }
+}
+
+/// Like `call_status`, but writes the value into `out`, which must not be null
+fn call_out<T>(out: *mut T, f: impl FnOnce() -> Result<T, MmsError>) -> MmsStatus {
+    if out.is_null() {
+        set_last_error("InvalidArgument: out pointer is null".to_string());
+        return MmsStatus::InvalidArgument;
+    }
+    match call(f) {
+        Ok(value) => {
+            unsafe { out.write(value) };
+            MmsStatus::Ok
+        }
+        Err(status) => status,
+    }
+}
+
+/// Leaks the vector, to be rebuilt by `vec_from_raw_parts` with the same element type. `length`
+/// and `capacity` count elements, not bytes.
+fn vec_into_raw_parts<T>(items: Vec<T>) -> (*mut T, i32, i32) {
+    let length = i32::try_from(items.len()).expect("buffer length cannot fit into a i32.");
+    let capacity = i32::try_from(items.capacity()).expect("buffer capacity cannot fit into a i32.");
+
+    // keep memory until call delete
+    let mut items = std::mem::ManuallyDrop::new(items);
+    (items.as_mut_ptr(), length, capacity)
+}
+
+/// Rebuilds a vector leaked by `vec_into_raw_parts`. A null pointer gives an empty vector. A
+/// negative length or capacity, or a length above the capacity, can only come from a buffer the
+/// caller changed; its memory is leaked and an empty vector returned, because this is called
+/// from `extern "C"` functions that must not panic.
+///
+/// # Safety
+/// The parts have to come from `vec_into_raw_parts::<T>` and must not be used afterwards.
+unsafe fn vec_from_raw_parts<T>(ptr: *mut T, length: i32, capacity: i32) -> Vec<T> {
+    match (usize::try_from(length), usize::try_from(capacity)) {
+        (Ok(length), Ok(capacity)) if !ptr.is_null() && length <= capacity => {
+            Vec::from_raw_parts(ptr, length, capacity)
+        }
+        _ => vec![],
+    }
+}
 
 #[repr(C)]
 pub struct ByteBuffer {
@@ -9,75 +142,280 @@ pub struct ByteBuffer {
     capacity: i32,
 }
 
-#[allow(dead_code)]
 impl ByteBuffer {
-    fn len(&self) -> usize {
-        self.length
-            .try_into()
-            .expect("buffer length negative or overflowed")
+    fn from_vec(bytes: Vec<u8>) -> Self {
+        let (ptr, length, capacity) = vec_into_raw_parts(bytes);
+        Self {
+            ptr,
+            length,
+            capacity,
+        }
     }
 
-    fn from_vec(bytes: Vec<u8>) -> Self {
-        let length = i32::try_from(bytes.len()).expect("buffer length cannot fit into a i32.");
-        let capacity =
-            i32::try_from(bytes.capacity()).expect("buffer capacity cannot fit into a i32.");
+    fn destroy_into_vec(self) -> Vec<u8> {
+        unsafe { vec_from_raw_parts(self.ptr, self.length, self.capacity) }
+    }
+
+    fn destroy(self) {
+        drop(self.destroy_into_vec());
+    }
+}
 
-        // keep memory until call delete
-        let mut v = std::mem::ManuallyDrop::new(bytes);
+/// An array of cells, free it with `mms_free_cell_buffer`. `length` and `capacity` count cells.
+#[repr(C)]
+pub struct CellBuffer {
+    ptr: *mut Cell,
+    length: i32,
+    capacity: i32,
+}
 
+impl CellBuffer {
+    fn from_vec(cells: Vec<Cell>) -> Self {
+        let (ptr, length, capacity) = vec_into_raw_parts(cells);
         Self {
-            ptr: v.as_mut_ptr(),
+            ptr,
             length,
             capacity,
         }
     }
 
-    fn from_vec_struct<T: Sized>(bytes: Vec<T>) -> Self {
-        let element_size = i32::try_from(std::mem::size_of::<T>()).unwrap();
-
-        let length = i32::try_from(bytes.len()).unwrap() * element_size;
-        let capacity = i32::try_from(bytes.capacity()).unwrap() * element_size;
+    fn destroy_into_vec(self) -> Vec<Cell> {
+        unsafe { vec_from_raw_parts(self.ptr, self.length, self.capacity) }
+    }
+}
 
-        let mut v = std::mem::ManuallyDrop::new(bytes);
+/// An array of stats, free it with `mms_free_stat_value_buffer`. `length` and `capacity` count
+/// stats.
+#[repr(C)]
+pub struct StatValueBuffer {
+    ptr: *mut StatValue,
+    length: i32,
+    capacity: i32,
+}
 
+impl StatValueBuffer {
+    fn from_vec(stats: Vec<StatValue>) -> Self {
+        let (ptr, length, capacity) = vec_into_raw_parts(stats);
         Self {
-            ptr: v.as_mut_ptr().cast::<u8>(),
+            ptr,
             length,
             capacity,
         }
     }
 
-    fn destroy_into_vec(self) -> Vec<u8> {
-        if self.ptr.is_null() {
-            vec![]
+    fn destroy_into_vec(self) -> Vec<StatValue> {
+        unsafe { vec_from_raw_parts(self.ptr, self.length, self.capacity) }
+    }
+}
+
+/// Whether a stat is counted in whole cells and turns or is a float
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatKind {
+    Int,
+    Float,
+}
+
+/// The value of a single stat
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatValue {
+    pub query: StatQuery,
+    pub kind: StatKind,
+    /// `false` while mms has no value yet, e.g. for the best run before the first run finished
+    pub has_value: bool,
+    /// The value of integer stats. `-1` without a value.
+    pub int_value: i32,
+    /// The value of float stats, and of integer stats converted to float. `-1` without a value.
+    pub float_value: f32,
+}
+
+impl From<Stat> for StatValue {
+    #[allow(clippy::cast_precision_loss)]
+    fn from(stat: Stat) -> Self {
+        let kind = if matches!(
+            stat,
+            Stat::TotalEffectiveDistance(_)
+                | Stat::BestRunEffectiveDistance(_)
+                | Stat::CurrentRunEffectiveDistance(_)
+                | Stat::Score(_)
+        ) {
+            StatKind::Float
         } else {
-            let capacity: usize = self
-                .capacity
-                .try_into()
-                .expect("buffer capacity negative or overflowed");
-            let length: usize = self
-                .length
-                .try_into()
-                .expect("buffer length negative or overflowed");
+            StatKind::Int
+        };
+        let int_value = stat.int_value();
+        let float_value = stat.value();
+        Self {
+            query: stat.query(),
+            kind,
+            has_value: float_value.is_some(),
+            int_value: int_value.unwrap_or(-1),
+            float_value: float_value.unwrap_or(-1.0),
+        }
+    }
+}
+
+/// All stats at once, see `mms_get_all_stats`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RunStats {
+    pub total_distance: StatValue,
+    pub total_turns: StatValue,
+    pub best_run_distance: StatValue,
+    pub best_run_turns: StatValue,
+    pub current_run_distance: StatValue,
+    pub current_run_turns: StatValue,
+    pub total_effective_distance: StatValue,
+    pub best_run_effective_distance: StatValue,
+    pub current_run_effective_distance: StatValue,
+    pub score: StatValue,
+}
 
-            unsafe { Vec::from_raw_parts(self.ptr, length, capacity) }
+impl From<&stats::RunStats> for RunStats {
+    fn from(stats: &stats::RunStats) -> Self {
+        let [total_distance, total_turns, best_run_distance, best_run_turns, current_run_distance, current_run_turns, total_effective_distance, best_run_effective_distance, current_run_effective_distance, score] =
+            stats.stats().map(StatValue::from);
+        Self {
+            total_distance,
+            total_turns,
+            best_run_distance,
+            best_run_turns,
+            current_run_distance,
+            current_run_turns,
+            total_effective_distance,
+            best_run_effective_distance,
+            current_run_effective_distance,
+            score,
         }
     }
+}
 
-    fn destroy_into_vec_struct<T: Sized>(self) -> Vec<T> {
-        if self.ptr.is_null() {
-            vec![]
-        } else {
-            let element_size = i32::try_from(std::mem::size_of::<T>()).unwrap();
-            let length = usize::try_from(self.length * element_size).unwrap();
-            let capacity = usize::try_from(self.capacity * element_size).unwrap();
+/// Where the mouse is and which way it is facing. `heading` is a `Direction`, which is checked
+/// before use.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MmsPose {
+    pub cell: Cell,
+    pub heading: i32,
+}
 
-            unsafe { Vec::from_raw_parts(self.ptr.cast::<T>(), length, capacity) }
+impl From<maze::Pose> for MmsPose {
+    fn from(pose: maze::Pose) -> Self {
+        Self {
+            cell: pose.cell,
+            heading: pose.heading as i32,
         }
     }
+}
 
-    fn destroy(self) {
-        drop(self.destroy_into_vec());
+/// The size of the buffer passed to a `ReadLineCallback`. mms never sends longer lines.
+pub const READ_LINE_CAPACITY: usize = 256;
+
+/// Writes the next line received from mms into `buffer`, without the line break, and returns its
+/// length in bytes. Returns a negative value if reading failed.
+pub type ReadLineCallback =
+    Option<extern "C" fn(user_data: *mut c_void, buffer: *mut u8, capacity: i32) -> i32>;
+
+/// Sends the line, without the line break, to mms. Returns `0` on success and any other value if
+/// writing failed.
+pub type WriteLineCallback =
+    Option<extern "C" fn(user_data: *mut c_void, line_utf8: *const u8, line_len: i32) -> i32>;
+
+// The callbacks were checked for null when the handle was created
+struct Callbacks {
+    read_line: extern "C" fn(*mut c_void, *mut u8, i32) -> i32,
+    write_line: extern "C" fn(*mut c_void, *const u8, i32) -> i32,
+    user_data: *mut c_void,
+}
+
+impl LineTransport for Callbacks {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let len = i32::try_from(line.len())
+            .map_err(|_| std::io::Error::other("line does not fit into an i32"))?;
+        match (self.write_line)(self.user_data, line.as_ptr(), len) {
+            0 => Ok(()),
+            code => Err(std::io::Error::other(format!(
+                "write_line callback failed with {code}"
+            ))),
+        }
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        let mut buffer = vec![0; READ_LINE_CAPACITY];
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let len = (self.read_line)(
+            self.user_data,
+            buffer.as_mut_ptr(),
+            READ_LINE_CAPACITY as i32,
+        );
+        let len = usize::try_from(len)
+            .map_err(|_| std::io::Error::other(format!("read_line callback failed with {len}")))?;
+        if len > READ_LINE_CAPACITY {
+            return Err(std::io::Error::other(format!(
+                "read_line callback returned {len} bytes, more than the capacity of {READ_LINE_CAPACITY}"
+            )));
+        }
+        buffer.truncate(len);
+        String::from_utf8(buffer)
+            .map_err(|error| std::io::Error::new(std::io::ErrorKind::InvalidData, error))
+    }
+}
+
+type MmsConnection = Connection<Box<dyn LineTransport>>;
+
+/// A connection to mms, created with `mms_handle_new_stdio` or `mms_handle_new_callbacks` and
+/// freed with `mms_handle_free`. Every handle tracks its own stats.
+// Only a name for C, the pointers point to an `MmsConnection`
+pub struct MmsHandle;
+
+/// The value behind an opaque handle, which is a `*mut T` under a C friendly name
+fn inner<'a, H, T>(handle: *mut H) -> Result<&'a mut T, MmsStatus> {
+    match unsafe { handle.cast::<T>().as_mut() } {
+        Some(value) => Ok(value),
+        None => {
+            set_last_error("InvalidArgument: handle is null".to_string());
+            Err(MmsStatus::InvalidArgument)
+        }
+    }
+}
+
+fn invalid_argument(message: String) -> MmsStatus {
+    set_last_error(format!("InvalidArgument: {message}"));
+    MmsStatus::InvalidArgument
+}
+
+/// Converts an enum value passed from C, which may be out of range
+fn enum_arg<T: TryFrom<i32>>(value: i32, name: &str) -> Result<T, MmsStatus> {
+    T::try_from(value).map_err(|_| invalid_argument(format!("{value} is not a valid {name}")))
+}
+
+fn pose_arg(pose: MmsPose) -> Result<maze::Pose, MmsStatus> {
+    Ok(maze::Pose::new(
+        pose.cell,
+        enum_arg(pose.heading, "Direction")?,
+    ))
+}
+
+fn handle_status(
+    handle: *mut MmsHandle,
+    f: impl FnOnce(&mut MmsConnection) -> Result<(), MmsError>,
+) -> MmsStatus {
+    match inner(handle) {
+        Ok(connection) => call_status(|| f(connection)),
+        Err(status) => status,
+    }
+}
+
+fn handle_out<T>(
+    handle: *mut MmsHandle,
+    out: *mut T,
+    f: impl FnOnce(&mut MmsConnection) -> Result<T, MmsError>,
+) -> MmsStatus {
+    match inner(handle) {
+        Ok(connection) => call_out(out, || f(connection)),
+        Err(status) => status,
     }
 }
 
@@ -86,151 +424,359 @@ fn string_to_native(value: String) -> *mut ByteBuffer {
     Box::into_raw(Box::new(buf))
 }
 
-fn native_to_string(str_utf8: *const u8, str_len: i32) -> String {
-    let slice = unsafe { std::slice::from_raw_parts(str_utf8, usize::try_from(str_len).unwrap()) };
-    String::from_utf8(slice.to_vec()).unwrap()
+fn cells_to_native(cells: Vec<Cell>) -> *mut CellBuffer {
+    Box::into_raw(Box::new(CellBuffer::from_vec(cells)))
 }
 
+fn stat_values_to_native(stats: Vec<StatValue>) -> *mut StatValueBuffer {
+    Box::into_raw(Box::new(StatValueBuffer::from_vec(stats)))
+}
+
+/// Copies a string given as pointer and length in bytes. A null pointer is only accepted with a
+/// length of `0`.
+fn native_to_string(str_utf8: *const u8, str_len: i32) -> Result<String, MmsStatus> {
+    let Ok(len) = usize::try_from(str_len) else {
+        return Err(invalid_argument(format!(
+            "string length {str_len} is negative"
+        )));
+    };
+    if str_utf8.is_null() {
+        return if len == 0 {
+            Ok(String::new())
+        } else {
+            Err(invalid_argument(format!(
+                "string is null but has a length of {len}"
+            )))
+        };
+    }
+    let slice = unsafe { std::slice::from_raw_parts(str_utf8, len) };
+    utf8_to_string(slice)
+}
+
+/// Copies a NUL-terminated string
+fn c_str_to_string(str_utf8: *const c_char) -> Result<String, MmsStatus> {
+    if str_utf8.is_null() {
+        return Err(invalid_argument("string is null".to_string()));
+    }
+    utf8_to_string(unsafe { CStr::from_ptr(str_utf8) }.to_bytes())
+}
+
+fn utf8_to_string(bytes: &[u8]) -> Result<String, MmsStatus> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|error| invalid_argument(format!("string is not valid UTF-8: {error}")))
+}
+
+/// Like `handle_status`, but fails with the status of `arg` before touching the connection
+fn handle_arg_status<A>(
+    handle: *mut MmsHandle,
+    arg: Result<A, MmsStatus>,
+    f: impl FnOnce(&mut MmsConnection, A) -> Result<(), MmsError>,
+) -> MmsStatus {
+    match arg {
+        Ok(arg) => handle_status(handle, |mms| f(mms, arg)),
+        Err(status) => status,
+    }
+}
+
+/// Like `handle_out`, but fails with the status of `arg` before touching the connection
+fn handle_arg_out<A, T>(
+    handle: *mut MmsHandle,
+    arg: Result<A, MmsStatus>,
+    out: *mut T,
+    f: impl FnOnce(&mut MmsConnection, A) -> Result<T, MmsError>,
+) -> MmsStatus {
+    match arg {
+        Ok(arg) => handle_out(handle, out, |mms| f(mms, arg)),
+        Err(status) => status,
+    }
+}
+
+/// The message of the last failed call on this thread, or null if no call failed yet. Free it
+/// with `mms_free_byte_buffer`.
 #[no_mangle]
-pub extern "C" fn maze_width() -> i32 {
-    Api::maze_width()
+pub extern "C" fn mms_last_error_message() -> *mut ByteBuffer {
+    LAST_ERROR
+        .with(|last| last.borrow().clone())
+        .map_or(std::ptr::null_mut(), string_to_native)
 }
 
+/// Creates a handle that talks to mms over `stdin` and `stdout`
 #[no_mangle]
-pub extern "C" fn maze_height() -> i32 {
-    Api::maze_height()
+pub extern "C" fn mms_handle_new_stdio() -> *mut MmsHandle {
+    into_handle(|| MmsConnection::new(Box::new(Stdio)))
 }
 
+/// Creates a handle that talks to mms through the callbacks. `user_data` is passed to every call
+/// and has to stay valid until the handle is freed. Returns null if a callback is null.
 #[no_mangle]
-pub extern "C" fn wall_front() -> bool {
-    Api::wall_front()
+pub extern "C" fn mms_handle_new_callbacks(
+    read_line: ReadLineCallback,
+    write_line: WriteLineCallback,
+    user_data: *mut c_void,
+) -> *mut MmsHandle {
+    let (Some(read_line), Some(write_line)) = (read_line, write_line) else {
+        invalid_argument("the read_line and write_line callbacks must not be null".to_string());
+        return std::ptr::null_mut();
+    };
+    into_handle(|| {
+        MmsConnection::new(Box::new(Callbacks {
+            read_line,
+            write_line,
+            user_data,
+        }))
+    })
 }
 
+/// Frees a handle created by `mms_handle_new_stdio` or `mms_handle_new_callbacks`
 #[no_mangle]
-pub extern "C" fn wall_right() -> bool {
-    Api::wall_right()
+pub unsafe extern "C" fn mms_handle_free(handle: *mut MmsHandle) {
+    free_handle::<_, MmsConnection>(handle);
 }
 
 #[no_mangle]
-pub extern "C" fn wall_left() -> bool {
-    Api::wall_left()
+pub extern "C" fn mms_get_maze_width(handle: *mut MmsHandle, width: *mut i32) -> MmsStatus {
+    handle_out(handle, width, MmsConnection::maze_width)
 }
 
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn move_forward(distance: u32) {
-    Api::move_forward(if distance < 1 {
-        None
-    } else {
-        Some(NonZeroU32::new(distance).unwrap())
-    });
+pub extern "C" fn mms_get_maze_height(handle: *mut MmsHandle, height: *mut i32) -> MmsStatus {
+    handle_out(handle, height, MmsConnection::maze_height)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_wall_front(handle: *mut MmsHandle, wall: *mut bool) -> MmsStatus {
+    handle_out(handle, wall, MmsConnection::wall_front)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_wall_right(handle: *mut MmsHandle, wall: *mut bool) -> MmsStatus {
+    handle_out(handle, wall, MmsConnection::wall_right)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_wall_left(handle: *mut MmsHandle, wall: *mut bool) -> MmsStatus {
+    handle_out(handle, wall, MmsConnection::wall_left)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_move_forward(handle: *mut MmsHandle, distance: u32) -> MmsStatus {
+    handle_status(handle, |mms| mms.move_forward(NonZeroU32::new(distance)))
+}
+
+#[no_mangle]
+pub extern "C" fn mms_turn_right(handle: *mut MmsHandle) -> MmsStatus {
+    handle_status(handle, MmsConnection::turn_right)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_turn_left(handle: *mut MmsHandle) -> MmsStatus {
+    handle_status(handle, MmsConnection::turn_left)
+}
+
+/// `direction` is a `Direction`
+#[no_mangle]
+pub extern "C" fn mms_set_wall(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    direction: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        enum_arg::<Direction>(direction, "Direction"),
+        |mms, direction| mms.set_wall(x, y, &direction),
+    )
 }
 
+/// Deprecated: use `mms_set_wall`, which takes a `Direction` instead of a string
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn turn_right() {
-    Api::turn_right();
+#[deprecated(note = "use `mms_set_wall`")]
+pub extern "C" fn set_wall(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    direction_utf8: *const u8,
+    direction_len: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        native_to_string(direction_utf8, direction_len),
+        |mms, direction| {
+            let direction = direction.parse()?;
+            mms.set_wall(x, y, &direction)
+        },
+    )
 }
 
+/// `direction` is a `Direction`
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn turn_left() {
-    Api::turn_left();
+pub extern "C" fn mms_clear_wall(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    direction: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        enum_arg::<Direction>(direction, "Direction"),
+        |mms, direction| mms.clear_wall(x, y, &direction),
+    )
 }
 
+/// Deprecated: use `mms_clear_wall`, which takes a `Direction` instead of a string
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn set_wall(x: u32, y: u32, direction_utf8: *const u8, direction_len: i32) {
-    Api::set_wall(
-        x,
-        y,
-        &native_to_string(direction_utf8, direction_len)
-            .parse()
-            .unwrap(),
-    );
+#[deprecated(note = "use `mms_clear_wall`")]
+pub extern "C" fn clear_wall(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    direction_utf8: *const u8,
+    direction_len: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        native_to_string(direction_utf8, direction_len),
+        |mms, direction| {
+            let direction = direction.parse()?;
+            mms.clear_wall(x, y, &direction)
+        },
+    )
 }
 
+/// `color` is a `CellColor`
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn clear_wall(x: u32, y: u32, direction_utf8: *const u8, direction_len: i32) {
-    Api::clear_wall(
-        x,
-        y,
-        &native_to_string(direction_utf8, direction_len)
-            .parse()
-            .unwrap(),
-    );
+pub extern "C" fn mms_set_color(handle: *mut MmsHandle, x: u32, y: u32, color: i32) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        enum_arg::<CellColor>(color, "CellColor"),
+        |mms, color| mms.set_color(x, y, &color),
+    )
 }
 
+/// Deprecated: use `mms_set_color`, which takes a `CellColor` instead of a string
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn set_color(x: u32, y: u32, color_utf8: *const u8, color_len: i32) {
-    Api::set_color(
-        x,
-        y,
-        &native_to_string(color_utf8, color_len).parse().unwrap(),
-    );
+#[deprecated(note = "use `mms_set_color`")]
+pub extern "C" fn set_color(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    color_utf8: *const u8,
+    color_len: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        native_to_string(color_utf8, color_len),
+        |mms, color| {
+            let color = color.parse()?;
+            mms.set_color(x, y, &color)
+        },
+    )
 }
 
+/// Parses the character mms uses for a color, e.g. `G` for `DarkGreen`, into `color`
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn clear_color(x: u32, y: u32) {
-    Api::clear_color(x, y);
+pub extern "C" fn mms_parse_color(
+    color_utf8: *const u8,
+    color_len: i32,
+    color: *mut CellColor,
+) -> MmsStatus {
+    guard(|| {
+        let text = native_to_string(color_utf8, color_len)?;
+        write(color, call(|| text.parse())?)
+    })
 }
 
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn clear_all_color() {
-    Api::clear_all_color();
+pub extern "C" fn mms_clear_color(handle: *mut MmsHandle, x: u32, y: u32) -> MmsStatus {
+    handle_status(handle, |mms| mms.clear_color(x, y))
 }
 
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn set_text(x: u32, y: u32, text_utf8: *const u8, text_len: i32) {
-    Api::set_text(x, y, &native_to_string(text_utf8, text_len));
+pub extern "C" fn mms_clear_all_color(handle: *mut MmsHandle) -> MmsStatus {
+    handle_status(handle, MmsConnection::clear_all_color)
 }
 
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn clear_text(x: u32, y: u32) {
-    Api::clear_text(x, y);
+pub extern "C" fn mms_set_text(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    text_utf8: *const u8,
+    text_len: i32,
+) -> MmsStatus {
+    handle_arg_status(
+        handle,
+        native_to_string(text_utf8, text_len),
+        |mms, text| mms.set_text(x, y, &text),
+    )
 }
 
+/// Like `mms_set_text`, but takes a NUL-terminated string
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn clear_all_text() {
-    Api::clear_all_text();
+pub extern "C" fn mms_set_text_cstr(
+    handle: *mut MmsHandle,
+    x: u32,
+    y: u32,
+    text: *const c_char,
+) -> MmsStatus {
+    handle_arg_status(handle, c_str_to_string(text), |mms, text| {
+        mms.set_text(x, y, &text)
+    })
 }
 
 #[no_mangle]
-pub extern "C" fn was_reset() -> bool {
-    Api::was_reset()
+pub extern "C" fn mms_clear_text(handle: *mut MmsHandle, x: u32, y: u32) -> MmsStatus {
+    handle_status(handle, |mms| mms.clear_text(x, y))
 }
 
 #[no_mangle]
-#[allow(unused_must_use)]
-pub extern "C" fn ack_reset() {
-    Api::ack_reset();
+pub extern "C" fn mms_clear_all_text(handle: *mut MmsHandle) -> MmsStatus {
+    handle_status(handle, MmsConnection::clear_all_text)
 }
 
 #[no_mangle]
-pub unsafe extern "C" fn free_byte_buffer(buffer: *mut ByteBuffer) {
+pub extern "C" fn mms_was_reset(handle: *mut MmsHandle, reset: *mut bool) -> MmsStatus {
+    handle_out(handle, reset, MmsConnection::was_reset)
+}
+
+#[no_mangle]
+pub extern "C" fn mms_ack_reset(handle: *mut MmsHandle) -> MmsStatus {
+    handle_status(handle, MmsConnection::ack_reset)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mms_free_byte_buffer(buffer: *mut ByteBuffer) {
+    if buffer.is_null() {
+        return;
+    }
     let buf = Box::from_raw(buffer);
     // drop inner buffer, if you need Vec<u8>, use buf.destroy_into_vec() instead.
     buf.destroy();
 }
 
 #[no_mangle]
-pub extern "C" fn get_stat(query_utf8: *const u8, query_len: i32) -> *mut ByteBuffer {
+pub unsafe extern "C" fn mms_free_cell_buffer(buffer: *mut CellBuffer) {
+    if buffer.is_null() {
+        return;
+    }
+    drop(Box::from_raw(buffer).destroy_into_vec());
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mms_free_stat_value_buffer(buffer: *mut StatValueBuffer) {
+    if buffer.is_null() {
+        return;
+    }
+    drop(Box::from_raw(buffer).destroy_into_vec());
+}
+
+fn stat_text(mms: &mut MmsConnection, query: StatQuery) -> Result<*mut ByteBuffer, MmsError> {
     use Stat::{
         BestRunDistance, BestRunEffectiveDistance, BestRunTurns, CurrentRunDistance,
         CurrentRunEffectiveDistance, CurrentRunTurns, Score, TotalDistance, TotalEffectiveDistance,
         TotalTurns,
     };
-    let slice =
-        unsafe { std::slice::from_raw_parts(query_utf8, usize::try_from(query_len).unwrap()) };
-    let query = String::from_utf8(slice.to_vec()).unwrap();
-    let s = match Api::get_stat(&query.parse().unwrap()) {
+    let s = match mms.get_stat(&query)? {
         TotalDistance(i)
         | TotalTurns(i)
         | BestRunDistance(i)
@@ -242,6 +788,463 @@ pub extern "C" fn get_stat(query_utf8: *const u8, query_len: i32) -> *mut ByteBu
         | CurrentRunEffectiveDistance(f)
         | Score(f) => f.to_string(),
     };
+    Ok(string_to_native(s))
+}
+
+/// Writes the stat, `query` is a `StatQuery`, as a string into `value`. Free it with
+/// `mms_free_byte_buffer`.
+#[no_mangle]
+pub extern "C" fn mms_get_stat_text(
+    handle: *mut MmsHandle,
+    query: i32,
+    value: *mut *mut ByteBuffer,
+) -> MmsStatus {
+    handle_arg_out(handle, enum_arg(query, "StatQuery"), value, stat_text)
+}
+
+/// Deprecated: use `mms_get_stat_text`, which takes a `StatQuery` instead of a string
+#[no_mangle]
+#[deprecated(note = "use `mms_get_stat_text`")]
+pub extern "C" fn get_stat(
+    handle: *mut MmsHandle,
+    query_utf8: *const u8,
+    query_len: i32,
+    value: *mut *mut ByteBuffer,
+) -> MmsStatus {
+    match native_to_string(query_utf8, query_len) {
+        Ok(query) => handle_out(handle, value, |mms| stat_text(mms, query.parse()?)),
+        Err(status) => status,
+    }
+}
+
+/// Writes the value of the stat, `query` is a `StatQuery`, into `value`, without any string
+/// parsing or freeing
+#[no_mangle]
+pub extern "C" fn mms_get_stat(
+    handle: *mut MmsHandle,
+    query: i32,
+    value: *mut StatValue,
+) -> MmsStatus {
+    handle_arg_out(handle, enum_arg(query, "StatQuery"), value, |mms, query| {
+        let stat = mms.get_stat(&query)?;
+        Ok(StatValue::from(stat))
+    })
+}
+
+/// Queries all stats and writes them into `stats`
+#[no_mangle]
+pub extern "C" fn mms_get_all_stats(handle: *mut MmsHandle, stats: *mut RunStats) -> MmsStatus {
+    handle_out(handle, stats, |mms| {
+        let fetched = stats::RunStats::fetch_from(mms)?;
+        Ok(RunStats::from(&fetched))
+    })
+}
+
+/// Queries the given stats, or all of them without queries, i.e. null or a length of `0`, and
+/// writes them into `stats` in the same order. `queries` are `StatQuery` values. A negative
+/// `queries_len` fails with `InvalidArgument`. Free `stats` with `mms_free_stat_value_buffer`.
+#[no_mangle]
+pub extern "C" fn mms_get_stats(
+    handle: *mut MmsHandle,
+    queries: *const i32,
+    queries_len: i32,
+    stats: *mut *mut StatValueBuffer,
+) -> MmsStatus {
+    let queries = match usize::try_from(queries_len) {
+        Err(_) => Err(invalid_argument(format!(
+            "queries length {queries_len} is negative"
+        ))),
+        Ok(len) if len == 0 || queries.is_null() => Ok(StatQuery::ALL.to_vec()),
+        Ok(len) => unsafe { std::slice::from_raw_parts(queries, len) }
+            .iter()
+            .map(|&query| enum_arg(query, "StatQuery"))
+            .collect(),
+    };
+    handle_arg_out(handle, queries, stats, |mms, queries: Vec<StatQuery>| {
+        let mut values = Vec::with_capacity(queries.len());
+        for query in queries {
+            values.push(StatValue::from(mms.get_stat(&query)?));
+        }
+        Ok(stat_values_to_native(values))
+    })
+}
+
+/// The knowledge about the maze, see `mms_maze_new`
+// Only a name for C, the pointers point to a `Maze`
+pub struct MazeHandle;
+
+/// Tracks the pose of the mouse, see `mms_pose_new`
+// Only a name for C, the pointers point to a `Pose`
+pub struct PoseHandle;
+
+/// Flood fill distances and path planning, see `mms_solver_new`
+// Only a name for C, the pointers point to a `Solver`
+pub struct SolverHandle;
+
+struct Solver {
+    assumption: Assumption,
+    distances: Option<Grid<Option<u32>>>,
+}
+
+/// Runs `f`, catching panics
+fn guard(f: impl FnOnce() -> Result<(), MmsStatus>) -> MmsStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => MmsStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(payload) => {
+            set_last_error(panic_message(payload.as_ref()));
+            MmsStatus::Panic
+        }
+    }
+}
+
+/// Moves the value created by `f` to the heap, or returns null if `f` panics
+fn into_handle<H, T>(f: impl FnOnce() -> T) -> *mut H {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(value) => Box::into_raw(Box::new(value)).cast(),
+        Err(payload) => {
+            set_last_error(panic_message(payload.as_ref()));
+            std::ptr::null_mut()
+        }
+    }
+}
+
+unsafe fn free_handle<H, T>(handle: *mut H) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle.cast::<T>()));
+    }
+}
+
+fn write<T>(out: *mut T, value: T) -> Result<(), MmsStatus> {
+    if out.is_null() {
+        return Err(invalid_argument("out pointer is null".to_string()));
+    }
+    unsafe { out.write(value) };
+    Ok(())
+}
+
+fn check_cells(maze: &Maze, cells: &[Cell]) -> Result<(), MmsStatus> {
+    match cells.iter().find(|&&cell| !maze.contains(cell)) {
+        Some(cell) => Err(invalid_argument(format!(
+            "cell ({}, {}) is outside of the {}x{} maze",
+            cell.x,
+            cell.y,
+            maze.width(),
+            maze.height()
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn maze_ref<'a>(maze: *mut MazeHandle, cells: &[Cell]) -> Result<&'a mut Maze, MmsStatus> {
+    let maze: &mut Maze = inner(maze)?;
+    check_cells(maze, cells)?;
+    Ok(maze)
+}
+
+/// The targets passed from C, or the goal cells if there are none
+fn targets(maze: &Maze, targets: *const Cell, targets_len: u32) -> Result<Vec<Cell>, MmsStatus> {
+    let targets = if targets.is_null() || targets_len == 0 {
+        maze.goals()
+    } else {
+        unsafe { std::slice::from_raw_parts(targets, targets_len as usize) }.to_vec()
+    };
+    check_cells(maze, &targets)?;
+    Ok(targets)
+}
+
+/// Creates an empty maze of the given size, with only the outer walls known
+#[no_mangle]
+pub extern "C" fn mms_maze_new(width: u32, height: u32) -> *mut MazeHandle {
+    into_handle(|| Maze::new(width, height))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mms_maze_free(maze: *mut MazeHandle) {
+    free_handle::<_, Maze>(maze);
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_width(maze: *mut MazeHandle, width: *mut u32) -> MmsStatus {
+    guard(|| write(width, maze_ref(maze, &[])?.width()))
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_height(maze: *mut MazeHandle, height: *mut u32) -> MmsStatus {
+    guard(|| write(height, maze_ref(maze, &[])?.height()))
+}
+
+/// Records a sensed wall on both of its sides
+#[no_mangle]
+pub extern "C" fn mms_maze_record_wall(
+    maze: *mut MazeHandle,
+    cell: Cell,
+    direction: i32,
+    present: bool,
+) -> MmsStatus {
+    guard(|| {
+        let direction = enum_arg(direction, "Direction")?;
+        maze_ref(maze, &[cell])?.record_wall(cell, direction, present);
+        Ok(())
+    })
+}
+
+/// Records the three sensor readings taken at the pose and marks its cell as visited
+#[no_mangle]
+pub extern "C" fn mms_maze_record_sensors(
+    maze: *mut MazeHandle,
+    pose: MmsPose,
+    front: bool,
+    left: bool,
+    right: bool,
+) -> MmsStatus {
+    guard(|| {
+        let pose = pose_arg(pose)?;
+        maze_ref(maze, &[pose.cell])?.record_sensors(&pose, front, left, right);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_wall(
+    maze: *mut MazeHandle,
+    cell: Cell,
+    direction: i32,
+    wall: *mut WallState,
+) -> MmsStatus {
+    guard(|| {
+        let direction = enum_arg(direction, "Direction")?;
+        write(wall, maze_ref(maze, &[cell])?.wall(cell, direction))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_mark_visited(maze: *mut MazeHandle, cell: Cell) -> MmsStatus {
+    guard(|| {
+        maze_ref(maze, &[cell])?.mark_visited(cell);
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_is_visited(
+    maze: *mut MazeHandle,
+    cell: Cell,
+    visited: *mut bool,
+) -> MmsStatus {
+    guard(|| write(visited, maze_ref(maze, &[cell])?.is_visited(cell)))
+}
+
+/// Writes the goal cells in the center of the maze into `goals`. Free it with
+/// `mms_free_cell_buffer`.
+#[no_mangle]
+pub extern "C" fn mms_maze_goals(maze: *mut MazeHandle, goals: *mut *mut CellBuffer) -> MmsStatus {
+    guard(|| {
+        write(goals, std::ptr::null_mut())?;
+        write(goals, cells_to_native(maze_ref(maze, &[])?.goals()))
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_maze_is_goal(
+    maze: *mut MazeHandle,
+    cell: Cell,
+    goal: *mut bool,
+) -> MmsStatus {
+    guard(|| write(goal, maze_ref(maze, &[cell])?.is_goal(cell)))
+}
+
+/// Creates a pose tracker at the start: `(0, 0)` facing north
+#[no_mangle]
+pub extern "C" fn mms_pose_new() -> *mut PoseHandle {
+    into_handle(maze::Pose::start)
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mms_pose_free(tracker: *mut PoseHandle) {
+    free_handle::<_, maze::Pose>(tracker);
+}
+
+#[no_mangle]
+pub extern "C" fn mms_pose_get(tracker: *mut PoseHandle, pose: *mut MmsPose) -> MmsStatus {
+    guard(|| write(pose, MmsPose::from(*inner::<_, maze::Pose>(tracker)?)))
+}
+
+#[no_mangle]
+pub extern "C" fn mms_pose_set(tracker: *mut PoseHandle, pose: MmsPose) -> MmsStatus {
+    guard(|| {
+        let pose = pose_arg(pose)?;
+        *inner::<_, maze::Pose>(tracker)? = pose;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_pose_turn_left(tracker: *mut PoseHandle) -> MmsStatus {
+    guard(|| {
+        inner::<_, maze::Pose>(tracker)?.turn_left();
+        Ok(())
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn mms_pose_turn_right(tracker: *mut PoseHandle) -> MmsStatus {
+    guard(|| {
+        inner::<_, maze::Pose>(tracker)?.turn_right();
+        Ok(())
+    })
+}
+
+/// Tracks a `mms_move_forward` over the given number of cells
+#[no_mangle]
+pub extern "C" fn mms_pose_move_forward(tracker: *mut PoseHandle, distance: u32) -> MmsStatus {
+    guard(|| {
+        inner::<_, maze::Pose>(tracker)?.move_forward(distance);
+        Ok(())
+    })
+}
+
+/// Creates a solver that treats unknown walls according to the `Assumption`. Returns null if it
+/// is out of range.
+#[no_mangle]
+pub extern "C" fn mms_solver_new(assumption: i32) -> *mut SolverHandle {
+    let Ok(assumption) = enum_arg(assumption, "Assumption") else {
+        return std::ptr::null_mut();
+    };
+    into_handle(|| Solver {
+        assumption,
+        distances: None,
+    })
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn mms_solver_free(solver: *mut SolverHandle) {
+    free_handle::<_, Solver>(solver);
+}
+
+/// Writes the `Assumption` the solver was created with into `assumption`
+#[no_mangle]
+pub extern "C" fn mms_solver_assumption(
+    solver: *mut SolverHandle,
+    assumption: *mut Assumption,
+) -> MmsStatus {
+    guard(|| write(assumption, inner::<_, Solver>(solver)?.assumption))
+}
+
+/// Computes the distance of every cell to the nearest target. Without targets, i.e. null or a
+/// length of `0`, the goal cells are used.
+#[no_mangle]
+pub extern "C" fn mms_solver_flood(
+    solver: *mut SolverHandle,
+    maze: *mut MazeHandle,
+    targets: *const Cell,
+    targets_len: u32,
+) -> MmsStatus {
+    guard(|| {
+        let solver: &mut Solver = inner(solver)?;
+        let maze = maze_ref(maze, &[])?;
+        let targets = self::targets(maze, targets, targets_len)?;
+        solver.distances = Some(solver::flood(maze, &targets, solver.assumption));
+        Ok(())
+    })
+}
+
+/// The distance of the cell to the nearest target of the last `mms_solver_flood`, or `-1` if it
+/// cannot reach any target or nothing was flooded yet
+#[no_mangle]
+pub extern "C" fn mms_solver_distance(
+    solver: *mut SolverHandle,
+    cell: Cell,
+    distance: *mut i64,
+) -> MmsStatus {
+    guard(|| {
+        let solver: &mut Solver = inner(solver)?;
+        let flooded = solver
+            .distances
+            .as_ref()
+            .and_then(|distances| distances.get(cell).copied().flatten());
+        write(distance, flooded.map_or(-1, i64::from))
+    })
+}
+
+/// The direction of the next step from the pose towards the targets of the last
+/// `mms_solver_flood`. `has_step` is `false` at a target or if no target can be reached.
+#[no_mangle]
+pub extern "C" fn mms_solver_next_step(
+    solver: *mut SolverHandle,
+    maze: *mut MazeHandle,
+    pose: MmsPose,
+    direction: *mut Direction,
+    has_step: *mut bool,
+) -> MmsStatus {
+    guard(|| {
+        let pose = pose_arg(pose)?;
+        let solver: &mut Solver = inner(solver)?;
+        let maze = maze_ref(maze, &[pose.cell])?;
+        let step = solver
+            .distances
+            .as_ref()
+            .and_then(|distances| solver::next_step(maze, distances, &pose, solver.assumption));
+        write(has_step, step.is_some())?;
+        match step {
+            Some(step) => write(direction, step),
+            None => Ok(()),
+        }
+    })
+}
 
-    string_to_native(s)
+/// Plans the shortest path from the pose to the nearest target, including the start and the final
+/// cell. Without targets, i.e. null or a length of `0`, the goal cells are used.
+///
+/// Writes up to `capacity` cells into `path` and the full length into `path_len`, which is `0` if
+/// no target can be reached. Call it again with a larger array if `path_len` exceeds `capacity`.
+#[no_mangle]
+pub extern "C" fn mms_solver_plan_path(
+    solver: *mut SolverHandle,
+    maze: *mut MazeHandle,
+    pose: MmsPose,
+    targets: *const Cell,
+    targets_len: u32,
+    path: *mut Cell,
+    capacity: u32,
+    path_len: *mut u32,
+) -> MmsStatus {
+    guard(|| {
+        let pose = pose_arg(pose)?;
+        let solver: &mut Solver = inner(solver)?;
+        let maze = maze_ref(maze, &[pose.cell])?;
+        let targets = self::targets(maze, targets, targets_len)?;
+        if path.is_null() && capacity > 0 {
+            return Err(invalid_argument("path is null".to_string()));
+        }
+        let planned =
+            solver::shortest_path(maze, &pose, &targets, solver.assumption).unwrap_or_default();
+        for (i, &cell) in planned.iter().take(capacity as usize).enumerate() {
+            unsafe { path.add(i).write(cell) };
+        }
+        write(path_len, u32::try_from(planned.len()).unwrap_or(u32::MAX))
+    })
+}
+
+/// Plans the shortest path like `mms_solver_plan_path`, but writes it into a new buffer, which is
+/// empty if no target can be reached. Free it with `mms_free_cell_buffer`.
+#[no_mangle]
+pub extern "C" fn mms_solver_path(
+    solver: *mut SolverHandle,
+    maze: *mut MazeHandle,
+    pose: MmsPose,
+    targets: *const Cell,
+    targets_len: u32,
+    path: *mut *mut CellBuffer,
+) -> MmsStatus {
+    guard(|| {
+        write(path, std::ptr::null_mut())?;
+        let pose = pose_arg(pose)?;
+        let solver: &mut Solver = inner(solver)?;
+        let maze = maze_ref(maze, &[pose.cell])?;
+        let targets = self::targets(maze, targets, targets_len)?;
+        let planned =
+            solver::shortest_path(maze, &pose, &targets, solver.assumption).unwrap_or_default();
+        write(path, cells_to_native(planned))
+    })
 }
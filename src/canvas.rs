@@ -0,0 +1,533 @@
+//! A cache of what the simulator currently shows, which only sends commands that change
+//! something.
+//!
+//! Bots tend to redraw colors and texts on every step, but most of those commands repeat what
+//! is already on screen. The [`Canvas`] remembers the color, text and walls of every cell and
+//! skips redundant commands. With [`Canvas::present`] a whole frame can be drawn at once, using
+//! `clear_all_color` and `clear_all_text` when that needs fewer commands than clearing cells
+//! one by one.
+//!
+//! A canvas draws through [`MmsApi`] by default, or through any other [`DrawTarget`], such as a
+//! [`Connection`].
+
+use crate::{
+    api,
+    connection::{Connection, LineTransport},
+    grid::Grid,
+    maze::Cell,
+    text::{CellText, TextFormat},
+    CellColor, Direction, MmsApi, ResultType,
+};
+
+/// Where a [`Canvas`] sends the commands that change the display. The methods behave like the
+/// ones of [`MmsApi`].
+pub trait DrawTarget {
+    /// See [`MmsApi::set_color`]
+    fn set_color(&mut self, x: u32, y: u32, color: &CellColor) -> ResultType<()>;
+    /// See [`MmsApi::clear_color`]
+    fn clear_color(&mut self, x: u32, y: u32) -> ResultType<()>;
+    /// See [`MmsApi::clear_all_color`]
+    fn clear_all_color(&mut self) -> ResultType<()>;
+    /// See [`MmsApi::set_text`]
+    fn set_text(&mut self, x: u32, y: u32, text: &str) -> ResultType<()>;
+    /// See [`MmsApi::clear_text`]
+    fn clear_text(&mut self, x: u32, y: u32) -> ResultType<()>;
+    /// See [`MmsApi::clear_all_text`]
+    fn clear_all_text(&mut self) -> ResultType<()>;
+    /// See [`MmsApi::set_wall`]
+    fn set_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()>;
+    /// See [`MmsApi::clear_wall`]
+    fn clear_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()>;
+}
+
+/// Draws over `stdin` and `stdout`
+impl DrawTarget for MmsApi {
+    fn set_color(&mut self, x: u32, y: u32, color: &CellColor) -> ResultType<()> {
+        MmsApi::set_color(x, y, color)
+    }
+
+    fn clear_color(&mut self, x: u32, y: u32) -> ResultType<()> {
+        MmsApi::clear_color(x, y)
+    }
+
+    fn clear_all_color(&mut self) -> ResultType<()> {
+        MmsApi::clear_all_color()
+    }
+
+    fn set_text(&mut self, x: u32, y: u32, text: &str) -> ResultType<()> {
+        MmsApi::set_text(x, y, text)
+    }
+
+    fn clear_text(&mut self, x: u32, y: u32) -> ResultType<()> {
+        MmsApi::clear_text(x, y)
+    }
+
+    fn clear_all_text(&mut self) -> ResultType<()> {
+        MmsApi::clear_all_text()
+    }
+
+    fn set_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()> {
+        MmsApi::set_wall(x, y, direction)
+    }
+
+    fn clear_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()> {
+        MmsApi::clear_wall(x, y, direction)
+    }
+}
+
+/// Draws over the transport of the connection. Errors panic with `use_panics`, like the ones of
+/// [`MmsApi`].
+impl<T: LineTransport> DrawTarget for Connection<T> {
+    fn set_color(&mut self, x: u32, y: u32, color: &CellColor) -> ResultType<()> {
+        api(Connection::set_color(self, x, y, color))
+    }
+
+    fn clear_color(&mut self, x: u32, y: u32) -> ResultType<()> {
+        api(Connection::clear_color(self, x, y))
+    }
+
+    fn clear_all_color(&mut self) -> ResultType<()> {
+        api(Connection::clear_all_color(self))
+    }
+
+    fn set_text(&mut self, x: u32, y: u32, text: &str) -> ResultType<()> {
+        api(Connection::set_text(self, x, y, text))
+    }
+
+    fn clear_text(&mut self, x: u32, y: u32) -> ResultType<()> {
+        api(Connection::clear_text(self, x, y))
+    }
+
+    fn clear_all_text(&mut self) -> ResultType<()> {
+        api(Connection::clear_all_text(self))
+    }
+
+    fn set_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()> {
+        api(Connection::set_wall(self, x, y, direction))
+    }
+
+    fn clear_wall(&mut self, x: u32, y: u32, direction: &Direction) -> ResultType<()> {
+        api(Connection::clear_wall(self, x, y, direction))
+    }
+}
+
+/// What the simulator shows for a single cell
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CellDisplay {
+    /// The cell color, `None` if cleared
+    pub color: Option<CellColor>,
+    /// The cell text, empty if cleared
+    pub text: String,
+    /// The displayed walls, indexed by `Direction`
+    pub walls: [bool; 4],
+}
+
+/// A full picture of the simulator display
+pub type Frame = Grid<CellDisplay>;
+
+/// Mirrors the simulator display and only emits the commands that change it.
+///
+/// Every drawing command has to go through the canvas, otherwise it gets out of sync with what
+/// the simulator shows. Cells outside the maze are ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Canvas<D = MmsApi> {
+    shown: Frame,
+    target: D,
+}
+
+impl Canvas {
+    /// Creates a canvas for an empty display of the given size, drawn through [`MmsApi`]
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self::with_target(width, height, MmsApi)
+    }
+}
+
+impl<D: DrawTarget> Canvas<D> {
+    /// Creates a canvas for an empty display of the given size, drawn through the target
+    #[must_use]
+    pub fn with_target(width: u32, height: u32, target: D) -> Self {
+        Self {
+            shown: Grid::new(width, height, CellDisplay::default()),
+            target,
+        }
+    }
+
+    /// Where the canvas draws
+    #[must_use]
+    pub fn target(&self) -> &D {
+        &self.target
+    }
+
+    /// Where the canvas draws. Commands sent through it directly bypass the cache.
+    #[must_use]
+    pub fn target_mut(&mut self) -> &mut D {
+        &mut self.target
+    }
+
+    /// What the simulator currently shows
+    #[must_use]
+    pub fn shown(&self) -> &Frame {
+        &self.shown
+    }
+
+    /// A copy of the current display, to be modified and passed to [`Canvas::present`]
+    #[must_use]
+    pub fn frame(&self) -> Frame {
+        self.shown.clone()
+    }
+
+    /// Set the color of the cell
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn set_color(&mut self, cell: Cell, color: CellColor) -> ResultType<()> {
+        if let Some(shown) = self.shown.get_mut(cell) {
+            if shown.color != Some(color) {
+                try_api!(self.target.set_color(cell.x, cell.y, &color));
+                shown.color = Some(color);
+            }
+        }
+        return_result!(());
+    }
+
+    /// Clear the color of the cell
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear_color(&mut self, cell: Cell) -> ResultType<()> {
+        if let Some(shown) = self.shown.get_mut(cell) {
+            if shown.color.is_some() {
+                try_api!(self.target.clear_color(cell.x, cell.y));
+                shown.color = None;
+            }
+        }
+        return_result!(());
+    }
+
+    /// Clear the color of all cells
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear_all_color(&mut self) -> ResultType<()> {
+        if self.shown.iter().any(|(_, shown)| shown.color.is_some()) {
+            try_api!(self.target.clear_all_color());
+            for cell in self.shown.cells() {
+                self.shown[cell].color = None;
+            }
+        }
+        return_result!(());
+    }
+
+    /// Set the text of the cell. An empty text clears it.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn set_text(&mut self, cell: Cell, text: &str) -> ResultType<()> {
+        if text.is_empty() {
+            return self.clear_text(cell);
+        }
+        if let Some(shown) = self.shown.get_mut(cell) {
+            if shown.text != text {
+                try_api!(self.target.set_text(cell.x, cell.y, text));
+                text.clone_into(&mut shown.text);
+            }
+        }
+        return_result!(());
+    }
+
+    /// Writes every value of the grid into its cell, formatted to fit. Values that format to an
+    /// empty text, like `None`, clear the cell.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn set_text_grid<T: CellText>(
+        &mut self,
+        values: &Grid<T>,
+        format: &TextFormat,
+    ) -> ResultType<()> {
+        for (cell, value) in values.iter() {
+            try_api!(self.set_text(cell, &value.cell_text(format)));
+        }
+        return_result!(());
+    }
+
+    /// Clear the text of the cell
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear_text(&mut self, cell: Cell) -> ResultType<()> {
+        if let Some(shown) = self.shown.get_mut(cell) {
+            if !shown.text.is_empty() {
+                try_api!(self.target.clear_text(cell.x, cell.y));
+                shown.text.clear();
+            }
+        }
+        return_result!(());
+    }
+
+    /// Clear the text of all cells
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear_all_text(&mut self) -> ResultType<()> {
+        if self.shown.iter().any(|(_, shown)| !shown.text.is_empty()) {
+            try_api!(self.target.clear_all_text());
+            for cell in self.shown.cells() {
+                self.shown[cell].text.clear();
+            }
+        }
+        return_result!(());
+    }
+
+    /// Display or clear the wall on the given side of the cell. A wall is shared with the
+    /// neighbouring cell, so both sides are updated.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn show_wall(&mut self, cell: Cell, direction: Direction, present: bool) -> ResultType<()> {
+        let shown = self
+            .shown
+            .get(cell)
+            .map(|shown| shown.walls[direction.index()]);
+        if shown.is_some_and(|shown| shown != present) {
+            if present {
+                try_api!(self.target.set_wall(cell.x, cell.y, &direction));
+            } else {
+                try_api!(self.target.clear_wall(cell.x, cell.y, &direction));
+            }
+            self.shown[cell].walls[direction.index()] = present;
+            let neighbor = cell.step(direction);
+            if let Some(shown) = neighbor.and_then(|neighbor| self.shown.get_mut(neighbor)) {
+                shown.walls[direction.opposite().index()] = present;
+            }
+        }
+        return_result!(());
+    }
+
+    /// Display the wall on the given side of the cell
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn set_wall(&mut self, cell: Cell, direction: Direction) -> ResultType<()> {
+        self.show_wall(cell, direction, true)
+    }
+
+    /// Clear the wall on the given side of the cell
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear_wall(&mut self, cell: Cell, direction: Direction) -> ResultType<()> {
+        self.show_wall(cell, direction, false)
+    }
+
+    /// Draws the frame, sending only the commands needed to get from the current display to it.
+    /// Colors and texts are cleared with a single `clear_all_color` or `clear_all_text` when that
+    /// results in fewer commands. Cells outside the canvas are ignored.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn present(&mut self, frame: &Frame) -> ResultType<()> {
+        let cells: Vec<(Cell, &CellDisplay)> = frame
+            .iter()
+            .filter(|&(cell, _)| self.shown.contains(cell))
+            .collect();
+
+        let changed_colors = cells
+            .iter()
+            .filter(|(cell, target)| self.shown[*cell].color != target.color)
+            .count();
+        let colored = cells
+            .iter()
+            .filter(|(_, target)| target.color.is_some())
+            .count();
+        if 1 + colored < changed_colors {
+            try_api!(self.clear_all_color());
+        }
+
+        let changed_texts = cells
+            .iter()
+            .filter(|(cell, target)| self.shown[*cell].text != target.text)
+            .count();
+        let texted = cells
+            .iter()
+            .filter(|(_, target)| !target.text.is_empty())
+            .count();
+        if 1 + texted < changed_texts {
+            try_api!(self.clear_all_text());
+        }
+
+        for (cell, target) in cells {
+            match target.color {
+                Some(color) => try_api!(self.set_color(cell, color)),
+                None => try_api!(self.clear_color(cell)),
+            }
+            try_api!(self.set_text(cell, &target.text));
+            for direction in Direction::ALL {
+                try_api!(self.show_wall(cell, direction, target.walls[direction.index()]));
+            }
+        }
+        return_result!(());
+    }
+}
+
+/// A canvas that records the commands it sends, for the tests of everything drawing through one
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::{Canvas, Connection, LineTransport};
+
+    /// Remembers every line sent. Drawing commands are never answered.
+    #[derive(Debug, Default)]
+    pub(crate) struct Recorder(Vec<String>);
+
+    impl LineTransport for Recorder {
+        fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+            self.0.push(line.to_string());
+            Ok(())
+        }
+
+        fn read_line(&mut self) -> std::io::Result<String> {
+            Ok(String::new())
+        }
+    }
+
+    pub(crate) type TestCanvas = Canvas<Connection<Recorder>>;
+
+    pub(crate) fn canvas(width: u32, height: u32) -> TestCanvas {
+        Canvas::with_target(width, height, Connection::new(Recorder::default()))
+    }
+
+    /// The lines sent since the last call
+    pub(crate) fn sent(canvas: &mut TestCanvas) -> Vec<String> {
+        std::mem::take(&mut canvas.target_mut().transport_mut().0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        testing::{canvas, sent},
+        *,
+    };
+
+    #[test]
+    fn redundant_commands_are_skipped() {
+        let mut canvas = canvas(2, 2);
+        let cell = Cell::new(1, 0);
+        unwrap_api!(canvas.set_color(cell, CellColor::Red));
+        unwrap_api!(canvas.set_color(cell, CellColor::Red));
+        unwrap_api!(canvas.set_text(cell, "abc"));
+        unwrap_api!(canvas.set_text(cell, "abc"));
+        unwrap_api!(canvas.clear_color(Cell::new(0, 0)));
+        unwrap_api!(canvas.clear_text(Cell::new(0, 0)));
+        unwrap_api!(canvas.set_color(Cell::new(5, 5), CellColor::Red));
+        assert_eq!(sent(&mut canvas), ["setColor 1 0 r", "setText 1 0 abc"]);
+
+        unwrap_api!(canvas.set_color(cell, CellColor::DarkGreen));
+        unwrap_api!(canvas.set_text(cell, ""));
+        unwrap_api!(canvas.clear_color(cell));
+        unwrap_api!(canvas.clear_color(cell));
+        unwrap_api!(canvas.clear_all_color());
+        unwrap_api!(canvas.clear_all_text());
+        assert_eq!(
+            sent(&mut canvas),
+            ["setColor 1 0 G", "clearText 1 0", "clearColor 1 0"]
+        );
+        assert_eq!(canvas.shown()[cell], CellDisplay::default());
+    }
+
+    #[test]
+    fn walls_are_shared_with_the_neighbor() {
+        let mut canvas = canvas(2, 1);
+        unwrap_api!(canvas.set_wall(Cell::new(0, 0), Direction::East));
+        unwrap_api!(canvas.set_wall(Cell::new(1, 0), Direction::West));
+        unwrap_api!(canvas.clear_wall(Cell::new(1, 0), Direction::North));
+        assert_eq!(sent(&mut canvas), ["setWall 0 0 e"]);
+        assert!(canvas.shown()[Cell::new(1, 0)].walls[Direction::West.index()]);
+
+        unwrap_api!(canvas.clear_wall(Cell::new(1, 0), Direction::West));
+        assert_eq!(sent(&mut canvas), ["clearWall 1 0 w"]);
+        assert!(!canvas.shown()[Cell::new(0, 0)].walls[Direction::East.index()]);
+    }
+
+    #[test]
+    fn present_clears_everything_when_that_is_cheaper() {
+        let mut canvas = canvas(4, 1);
+        let mut frame = canvas.frame();
+        for cell in frame.cells() {
+            frame[cell].color = Some(CellColor::Red);
+            frame[cell].text = "x".to_string();
+        }
+        unwrap_api!(canvas.present(&frame));
+        assert_eq!(sent(&mut canvas).len(), 8);
+
+        let mut frame = canvas.frame();
+        for cell in frame.cells() {
+            frame[cell] = CellDisplay::default();
+        }
+        frame[Cell::new(2, 0)].color = Some(CellColor::Blue);
+        unwrap_api!(canvas.present(&frame));
+        assert_eq!(
+            sent(&mut canvas),
+            ["clearAllColor", "clearAllText", "setColor 2 0 b"]
+        );
+        assert_eq!(canvas.shown(), &frame);
+    }
+
+    #[test]
+    fn present_clears_single_cells_when_that_is_cheaper() {
+        let mut canvas = canvas(3, 1);
+        for x in 0..3 {
+            unwrap_api!(canvas.set_color(Cell::new(x, 0), CellColor::Red));
+        }
+        sent(&mut canvas);
+
+        // Two changed colors cost two commands, clearing all and redrawing one costs two too
+        let mut frame = canvas.frame();
+        frame[Cell::new(0, 0)].color = None;
+        frame[Cell::new(1, 0)].color = None;
+        unwrap_api!(canvas.present(&frame));
+        assert_eq!(sent(&mut canvas), ["clearColor 0 0", "clearColor 1 0"]);
+        assert_eq!(canvas.shown(), &frame);
+
+        unwrap_api!(canvas.present(&frame));
+        assert!(sent(&mut canvas).is_empty());
+    }
+
+    #[test]
+    fn text_grids_are_formatted_and_diffed() {
+        let mut canvas = canvas(3, 1);
+        let format = TextFormat::default();
+        let mut values = Grid::new(3, 1, Some(7));
+        values[Cell::new(1, 0)] = None;
+        unwrap_api!(canvas.set_text_grid(&values, &format));
+        assert_eq!(sent(&mut canvas), ["setText 0 0 7", "setText 2 0 7"]);
+
+        values[Cell::new(0, 0)] = None;
+        values[Cell::new(1, 0)] = Some(12_345);
+        unwrap_api!(canvas.set_text_grid(&values, &format));
+        assert_eq!(sent(&mut canvas), ["clearText 0 0", "setText 1 0 12345"]);
+    }
+}
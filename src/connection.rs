@@ -0,0 +1,461 @@
+//! The mms protocol over any line based transport.
+//!
+//! [`MmsApi`](crate::MmsApi) talks to mms over `stdin` and `stdout`. A [`Connection`] does the
+//! same over a [`LineTransport`], so a bot can also be driven in-process, over a socket or through
+//! callbacks of a host application.
+
+use std::{
+    io::{stdin, stdout, BufRead, Write},
+    num::NonZeroU32,
+};
+
+use crate::{
+    protocol::{Command, Response},
+    stats::StatsTracker,
+    CellColor, Direction, MmsError, Stat, StatQuery,
+};
+
+/// Sends commands to mms and receives the responses, one line at a time
+pub trait LineTransport {
+    /// Sends a single line, without the line break
+    ///
+    /// # Errors
+    /// Any I/O error of the underlying transport
+    fn write_line(&mut self, line: &str) -> std::io::Result<()>;
+
+    /// Receives a single line. An empty string means the other side is gone.
+    ///
+    /// # Errors
+    /// Any I/O error of the underlying transport
+    fn read_line(&mut self) -> std::io::Result<String>;
+}
+
+impl<T: LineTransport + ?Sized> LineTransport for Box<T> {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        (**self).write_line(line)
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        (**self).read_line()
+    }
+}
+
+/// The process' `stdin` and `stdout`, locked for each line
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stdio;
+
+impl LineTransport for Stdio {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let mut cout = stdout().lock();
+        writeln!(cout, "{line}")?;
+        cout.flush()
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        let mut response = String::new();
+        stdin().lock().read_line(&mut response)?;
+        Ok(response)
+    }
+}
+
+/// Any reader and writer, e.g. the pipes of a child process or both halves of a socket
+#[derive(Debug)]
+pub struct Streams<R, W> {
+    reader: R,
+    writer: W,
+}
+
+impl<R: BufRead, W: Write> Streams<R, W> {
+    #[must_use]
+    pub fn new(reader: R, writer: W) -> Self {
+        Self { reader, writer }
+    }
+
+    #[must_use]
+    pub fn get_ref(&self) -> (&R, &W) {
+        (&self.reader, &self.writer)
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> (R, W) {
+        (self.reader, self.writer)
+    }
+}
+
+impl<R: BufRead, W: Write> LineTransport for Streams<R, W> {
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        writeln!(self.writer, "{line}")?;
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> std::io::Result<String> {
+        let mut response = String::new();
+        self.reader.read_line(&mut response)?;
+        Ok(response)
+    }
+}
+
+/// The mms api over a [`LineTransport`]. The methods behave like the ones of
+/// [`MmsApi`](crate::MmsApi), which uses a `Connection<Stdio>`, but always return a `Result`,
+/// with or without `use_panics`. Every connection tracks its own stats.
+#[derive(Debug)]
+pub struct Connection<T> {
+    transport: T,
+    tracker: StatsTracker,
+}
+
+impl<T: LineTransport> Connection<T> {
+    #[must_use]
+    pub const fn new(transport: T) -> Self {
+        Self {
+            transport,
+            tracker: StatsTracker::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    #[must_use]
+    pub fn transport_mut(&mut self) -> &mut T {
+        &mut self.transport
+    }
+
+    /// The stats tracked from the commands sent over this connection
+    #[must_use]
+    pub fn tracker(&self) -> &StatsTracker {
+        &self.tracker
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), MmsError> {
+        Ok(self.transport.write_line(line)?)
+    }
+
+    fn request(&mut self, line: &str) -> Result<String, MmsError> {
+        self.send(line)?;
+        Ok(self.transport.read_line()?)
+    }
+
+    fn request_ack(&mut self, line: &str) -> Result<(), MmsError> {
+        let response = self.request(line)?;
+        if response.trim() == "ack" {
+            Ok(())
+        } else {
+            Err(MmsError::InvalidAck(response))
+        }
+    }
+
+    /// See [`MmsApi::maze_width`](crate::MmsApi::maze_width)
+    pub fn maze_width(&mut self) -> Result<i32, MmsError> {
+        let response = self.request("mazeWidth")?;
+        let width = response.trim().parse()?;
+        self.tracker.set_width(width);
+        Ok(width)
+    }
+
+    /// See [`MmsApi::maze_height`](crate::MmsApi::maze_height)
+    pub fn maze_height(&mut self) -> Result<i32, MmsError> {
+        let response = self.request("mazeHeight")?;
+        let height = response.trim().parse()?;
+        self.tracker.set_height(height);
+        Ok(height)
+    }
+
+    /// See [`MmsApi::wall_front`](crate::MmsApi::wall_front)
+    pub fn wall_front(&mut self) -> Result<bool, MmsError> {
+        let response = self.request("wallFront")?;
+        Ok(response.trim() == "true")
+    }
+
+    /// See [`MmsApi::wall_right`](crate::MmsApi::wall_right)
+    pub fn wall_right(&mut self) -> Result<bool, MmsError> {
+        let response = self.request("wallRight")?;
+        Ok(response.trim() == "true")
+    }
+
+    /// See [`MmsApi::wall_left`](crate::MmsApi::wall_left)
+    pub fn wall_left(&mut self) -> Result<bool, MmsError> {
+        let response = self.request("wallLeft")?;
+        Ok(response.trim() == "true")
+    }
+
+    /// See [`MmsApi::move_forward`](crate::MmsApi::move_forward)
+    pub fn move_forward(&mut self, distance: Option<NonZeroU32>) -> Result<(), MmsError> {
+        self.request_ack(&format!(
+            "moveForward {}",
+            distance.map_or_else(String::new, |d| d.to_string())
+        ))?;
+        self.tracker
+            .record_move(distance.map_or(1, NonZeroU32::get));
+        Ok(())
+    }
+
+    /// See [`MmsApi::turn_right`](crate::MmsApi::turn_right)
+    pub fn turn_right(&mut self) -> Result<(), MmsError> {
+        self.request_ack("turnRight")?;
+        self.tracker.record_turn_right();
+        Ok(())
+    }
+
+    /// See [`MmsApi::turn_left`](crate::MmsApi::turn_left)
+    pub fn turn_left(&mut self) -> Result<(), MmsError> {
+        self.request_ack("turnLeft")?;
+        self.tracker.record_turn_left();
+        Ok(())
+    }
+
+    /// See [`MmsApi::set_wall`](crate::MmsApi::set_wall)
+    pub fn set_wall(&mut self, x: u32, y: u32, direction: &Direction) -> Result<(), MmsError> {
+        self.send(&format!("setWall {x} {y} {}", direction.get_string()))
+    }
+
+    /// See [`MmsApi::clear_wall`](crate::MmsApi::clear_wall)
+    pub fn clear_wall(&mut self, x: u32, y: u32, direction: &Direction) -> Result<(), MmsError> {
+        self.send(&format!("clearWall {x} {y} {}", direction.get_string()))
+    }
+
+    /// See [`MmsApi::set_color`](crate::MmsApi::set_color)
+    pub fn set_color(&mut self, x: u32, y: u32, color: &CellColor) -> Result<(), MmsError> {
+        self.send(&format!("setColor {x} {y} {}", color.get_char()))
+    }
+
+    /// See [`MmsApi::clear_color`](crate::MmsApi::clear_color)
+    pub fn clear_color(&mut self, x: u32, y: u32) -> Result<(), MmsError> {
+        self.send(&format!("clearColor {x} {y}"))
+    }
+
+    /// See [`MmsApi::clear_all_color`](crate::MmsApi::clear_all_color)
+    pub fn clear_all_color(&mut self) -> Result<(), MmsError> {
+        self.send("clearAllColor")
+    }
+
+    /// See [`MmsApi::set_text`](crate::MmsApi::set_text)
+    pub fn set_text(&mut self, x: u32, y: u32, text: &str) -> Result<(), MmsError> {
+        self.send(&format!("setText {x} {y} {text}"))
+    }
+
+    /// See [`MmsApi::clear_text`](crate::MmsApi::clear_text)
+    pub fn clear_text(&mut self, x: u32, y: u32) -> Result<(), MmsError> {
+        self.send(&format!("clearText {x} {y}"))
+    }
+
+    /// See [`MmsApi::clear_all_text`](crate::MmsApi::clear_all_text)
+    pub fn clear_all_text(&mut self) -> Result<(), MmsError> {
+        self.send("clearAllText")
+    }
+
+    /// See [`MmsApi::was_reset`](crate::MmsApi::was_reset)
+    pub fn was_reset(&mut self) -> Result<bool, MmsError> {
+        let response = self.request("wasReset")?;
+        Ok(response.trim() == "true")
+    }
+
+    /// See [`MmsApi::ack_reset`](crate::MmsApi::ack_reset)
+    pub fn ack_reset(&mut self) -> Result<(), MmsError> {
+        self.request_ack("ackReset")?;
+        self.tracker.record_reset();
+        Ok(())
+    }
+
+    /// See [`MmsApi::get_stat`](crate::MmsApi::get_stat)
+    pub fn get_stat(&mut self, query: &StatQuery) -> Result<Stat, MmsError> {
+        let response = self.request(&format!("getStat {}", query.get_string()))?;
+        let response = response.trim();
+        let result = match query {
+            StatQuery::TotalDistance => Stat::TotalDistance(response.parse()?),
+            StatQuery::TotalTurns => Stat::TotalTurns(response.parse()?),
+            StatQuery::BestRunDistance => Stat::BestRunDistance(response.parse()?),
+            StatQuery::BestRunTurns => Stat::BestRunTurns(response.parse()?),
+            StatQuery::CurrentRunDistance => Stat::CurrentRunDistance(response.parse()?),
+            StatQuery::CurrentRunTurns => Stat::CurrentRunTurns(response.parse()?),
+            StatQuery::TotalEffectiveDistance => Stat::TotalEffectiveDistance(response.parse()?),
+            StatQuery::BestRunEffectiveDistance => {
+                Stat::BestRunEffectiveDistance(response.parse()?)
+            }
+            StatQuery::CurrentRunEffectiveDistance => {
+                Stat::CurrentRunEffectiveDistance(response.parse()?)
+            }
+            StatQuery::Score => Stat::Score(response.parse()?),
+        };
+        Ok(result)
+    }
+
+    /// Runs the command with the matching method above
+    pub fn execute(&mut self, command: &Command) -> Result<Response, MmsError> {
+        let response = match command {
+            Command::MazeWidth => Response::Size(self.maze_width()?),
+            Command::MazeHeight => Response::Size(self.maze_height()?),
+            Command::WallFront => Response::Bool(self.wall_front()?),
+            Command::WallRight => Response::Bool(self.wall_right()?),
+            Command::WallLeft => Response::Bool(self.wall_left()?),
+            Command::MoveForward { distance } => {
+                self.move_forward(*distance)?;
+                Response::Ack
+            }
+            Command::TurnRight => {
+                self.turn_right()?;
+                Response::Ack
+            }
+            Command::TurnLeft => {
+                self.turn_left()?;
+                Response::Ack
+            }
+            Command::SetWall { x, y, direction } => {
+                self.set_wall(*x, *y, direction)?;
+                Response::None
+            }
+            Command::ClearWall { x, y, direction } => {
+                self.clear_wall(*x, *y, direction)?;
+                Response::None
+            }
+            Command::SetColor { x, y, color } => {
+                self.set_color(*x, *y, color)?;
+                Response::None
+            }
+            Command::ClearColor { x, y } => {
+                self.clear_color(*x, *y)?;
+                Response::None
+            }
+            Command::ClearAllColor => {
+                self.clear_all_color()?;
+                Response::None
+            }
+            Command::SetText { x, y, text } => {
+                self.set_text(*x, *y, text)?;
+                Response::None
+            }
+            Command::ClearText { x, y } => {
+                self.clear_text(*x, *y)?;
+                Response::None
+            }
+            Command::ClearAllText => {
+                self.clear_all_text()?;
+                Response::None
+            }
+            Command::WasReset => Response::Bool(self.was_reset()?),
+            Command::AckReset => {
+                self.ack_reset()?;
+                Response::Ack
+            }
+            Command::GetStat { query } => Response::Stat(self.get_stat(query)?),
+        };
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Executes `commands` against the canned `responses` and returns what was sent
+    fn execute(responses: &str, commands: &[Command]) -> (Vec<Response>, String) {
+        let mut output = Vec::new();
+        let mut connection = Connection::new(Streams::new(responses.as_bytes(), &mut output));
+        let results = commands
+            .iter()
+            .map(|command| connection.execute(command).unwrap())
+            .collect();
+        (results, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn queries_send_a_line_and_parse_the_answer() {
+        let (responses, sent) = execute(
+            "16\n8\ntrue\nfalse\ntrue\ntrue\n42\n",
+            &[
+                Command::MazeWidth,
+                Command::MazeHeight,
+                Command::WallFront,
+                Command::WallRight,
+                Command::WallLeft,
+                Command::WasReset,
+                Command::GetStat {
+                    query: StatQuery::TotalDistance,
+                },
+            ],
+        );
+        assert_eq!(
+            responses,
+            [
+                Response::Size(16),
+                Response::Size(8),
+                Response::Bool(true),
+                Response::Bool(false),
+                Response::Bool(true),
+                Response::Bool(true),
+                Response::Stat(Stat::TotalDistance(42)),
+            ]
+        );
+        assert_eq!(
+            sent,
+            "mazeWidth\nmazeHeight\nwallFront\nwallRight\nwallLeft\nwasReset\n\
+             getStat total-distance\n"
+        );
+    }
+
+    #[test]
+    fn movements_wait_for_the_ack() {
+        let (responses, sent) = execute(
+            "ack\nack\nack\nack\n",
+            &[
+                Command::MoveForward {
+                    distance: NonZeroU32::new(2),
+                },
+                Command::TurnRight,
+                Command::TurnLeft,
+                Command::AckReset,
+            ],
+        );
+        assert_eq!(responses, [Response::Ack; 4]);
+        assert_eq!(sent, "moveForward 2\nturnRight\nturnLeft\nackReset\n");
+    }
+
+    #[test]
+    fn drawing_reads_nothing() {
+        let (responses, sent) = execute(
+            "",
+            &[
+                Command::SetWall {
+                    x: 1,
+                    y: 2,
+                    direction: Direction::North,
+                },
+                Command::ClearWall {
+                    x: 1,
+                    y: 2,
+                    direction: Direction::West,
+                },
+                Command::SetColor {
+                    x: 0,
+                    y: 3,
+                    color: CellColor::DarkGreen,
+                },
+                Command::ClearColor { x: 0, y: 3 },
+                Command::ClearAllColor,
+                Command::SetText {
+                    x: 4,
+                    y: 5,
+                    text: "hi".to_string(),
+                },
+                Command::ClearText { x: 4, y: 5 },
+                Command::ClearAllText,
+            ],
+        );
+        assert_eq!(responses, [Response::None; 8]);
+        assert_eq!(
+            sent,
+            "setWall 1 2 n\nclearWall 1 2 w\nsetColor 0 3 G\nclearColor 0 3\nclearAllColor\n\
+             setText 4 5 hi\nclearText 4 5\nclearAllText\n"
+        );
+    }
+
+    #[test]
+    fn a_crash_instead_of_an_ack_is_an_error() {
+        let mut output = Vec::new();
+        let mut connection = Connection::new(Streams::new(&b"crash\n"[..], &mut output));
+        let result = connection.execute(&Command::MoveForward { distance: None });
+        assert!(matches!(result, Err(MmsError::InvalidAck(response)) if response == "crash\n"));
+    }
+}
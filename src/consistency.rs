@@ -0,0 +1,233 @@
+//! Detects sensor readings that contradict walls recorded earlier.
+//!
+//! When the tracked pose drifts away from the real position of the mouse, new readings are
+//! recorded for the wrong cell and sooner or later disagree with what is already known.
+
+use std::fmt;
+
+use crate::{
+    canvas::{Canvas, DrawTarget},
+    layers::Layer,
+    maze::{Cell, Maze, Pose, WallState},
+    CellColor, Direction, ResultType,
+};
+
+/// A sensor reading that disagrees with a wall recorded earlier
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Contradiction {
+    pub cell: Cell,
+    pub direction: Direction,
+    /// What was known before the reading
+    pub known: WallState,
+    /// What the sensor reported
+    pub observed: WallState,
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "wall {:?} of ({}, {}) was {:?} but is now sensed as {:?}",
+            self.direction, self.cell.x, self.cell.y, self.known, self.observed
+        )
+    }
+}
+
+/// Which side wins when a reading contradicts a known wall
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ConflictPolicy {
+    /// Keep the wall that was recorded first
+    TrustOld,
+    /// Overwrite the wall with the new reading
+    #[default]
+    TrustNew,
+}
+
+/// Records a single wall reading and returns the contradiction if it disagrees with a known
+/// wall. The policy decides which state the maze keeps.
+pub fn check_wall(
+    maze: &mut Maze,
+    cell: Cell,
+    direction: Direction,
+    present: bool,
+    policy: ConflictPolicy,
+) -> Option<Contradiction> {
+    let known = maze.wall(cell, direction);
+    let observed = WallState::from(present);
+    let contradiction = (known.is_known() && known != observed).then_some(Contradiction {
+        cell,
+        direction,
+        known,
+        observed,
+    });
+    if contradiction.is_none() || policy == ConflictPolicy::TrustNew {
+        maze.set_wall(cell, direction, observed);
+    }
+    contradiction
+}
+
+/// Like [`Maze::record_sensors`], but returns every reading that contradicts a known wall
+pub fn check_sensors(
+    maze: &mut Maze,
+    pose: &Pose,
+    front: bool,
+    left: bool,
+    right: bool,
+    policy: ConflictPolicy,
+) -> Vec<Contradiction> {
+    let contradictions = [
+        (pose.heading, front),
+        (pose.heading.left(), left),
+        (pose.heading.right(), right),
+    ]
+    .into_iter()
+    .filter_map(|(direction, present)| check_wall(maze, pose.cell, direction, present, policy))
+    .collect();
+    maze.mark_visited(pose.cell);
+    contradictions
+}
+
+/// Marks the cell of every contradiction with `CellColor::Red` through the canvas, so it knows
+/// to redraw the cell later
+///
+/// # Errors
+/// `IoError`
+/// # Panics
+/// this panics when `use_panics` is disabled
+pub fn mark<D: DrawTarget>(
+    canvas: &mut Canvas<D>,
+    contradictions: &[Contradiction],
+) -> ResultType<()> {
+    for contradiction in contradictions {
+        try_api!(canvas.set_color(contradiction.cell, CellColor::Red));
+    }
+    return_result!(());
+}
+
+/// Marks the cell of every contradiction with `CellColor::Red` in a layer of a
+/// [`LayerStack`](crate::layers::LayerStack)
+pub fn mark_into(layer: &mut Layer, contradictions: &[Contradiction]) {
+    for contradiction in contradictions {
+        layer.set_color(contradiction.cell, CellColor::Red);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL: Cell = Cell::new(1, 1);
+
+    #[test]
+    fn unknown_and_agreeing_walls_are_no_contradiction() {
+        for policy in [ConflictPolicy::TrustOld, ConflictPolicy::TrustNew] {
+            let mut maze = Maze::new(4, 4);
+            assert_eq!(
+                check_wall(&mut maze, CELL, Direction::North, true, policy),
+                None
+            );
+            assert_eq!(maze.wall(CELL, Direction::North), WallState::Wall);
+            assert_eq!(
+                check_wall(&mut maze, CELL, Direction::North, true, policy),
+                None
+            );
+            assert_eq!(
+                check_wall(&mut maze, Cell::new(1, 2), Direction::South, true, policy),
+                None
+            );
+        }
+    }
+
+    #[test]
+    fn trust_old_keeps_the_known_wall() {
+        let mut maze = Maze::new(4, 4);
+        maze.record_wall(CELL, Direction::East, true);
+        assert_eq!(
+            check_wall(
+                &mut maze,
+                CELL,
+                Direction::East,
+                false,
+                ConflictPolicy::TrustOld
+            ),
+            Some(Contradiction {
+                cell: CELL,
+                direction: Direction::East,
+                known: WallState::Wall,
+                observed: WallState::Open,
+            })
+        );
+        assert_eq!(maze.wall(CELL, Direction::East), WallState::Wall);
+        assert_eq!(maze.wall(Cell::new(2, 1), Direction::West), WallState::Wall);
+    }
+
+    #[test]
+    fn trust_new_overwrites_the_known_wall() {
+        let mut maze = Maze::new(4, 4);
+        maze.record_wall(CELL, Direction::East, false);
+        assert_eq!(
+            check_wall(
+                &mut maze,
+                CELL,
+                Direction::East,
+                true,
+                ConflictPolicy::TrustNew
+            ),
+            Some(Contradiction {
+                cell: CELL,
+                direction: Direction::East,
+                known: WallState::Open,
+                observed: WallState::Wall,
+            })
+        );
+        assert_eq!(maze.wall(CELL, Direction::East), WallState::Wall);
+        assert_eq!(maze.wall(Cell::new(2, 1), Direction::West), WallState::Wall);
+    }
+
+    #[test]
+    fn sensors_are_checked_relative_to_the_heading() {
+        for policy in [ConflictPolicy::TrustOld, ConflictPolicy::TrustNew] {
+            let mut maze = Maze::new(4, 4);
+            maze.record_wall(CELL, Direction::West, true);
+            maze.record_wall(CELL, Direction::North, false);
+            let pose = Pose::new(CELL, Direction::East);
+
+            // Front is east and unknown, left is north and open, right is south and unknown
+            let contradictions = check_sensors(&mut maze, &pose, false, true, false, policy);
+            assert_eq!(
+                contradictions,
+                [Contradiction {
+                    cell: CELL,
+                    direction: Direction::North,
+                    known: WallState::Open,
+                    observed: WallState::Wall,
+                }]
+            );
+            let north = match policy {
+                ConflictPolicy::TrustOld => WallState::Open,
+                ConflictPolicy::TrustNew => WallState::Wall,
+            };
+            assert_eq!(maze.wall(CELL, Direction::North), north);
+            assert_eq!(maze.wall(CELL, Direction::East), WallState::Open);
+            assert_eq!(maze.wall(CELL, Direction::South), WallState::Open);
+            assert_eq!(maze.wall(CELL, Direction::West), WallState::Wall);
+            assert!(maze.is_visited(CELL));
+        }
+    }
+
+    #[test]
+    fn boundary_contradictions_never_open_the_boundary() {
+        let mut maze = Maze::new(4, 4);
+        let contradictions = check_sensors(
+            &mut maze,
+            &Pose::start(),
+            false,
+            false,
+            false,
+            ConflictPolicy::TrustNew,
+        );
+        assert_eq!(contradictions.len(), 1);
+        assert_eq!(contradictions[0].direction, Direction::West);
+        assert_eq!(maze.wall(Cell::new(0, 0), Direction::West), WallState::Wall);
+    }
+}
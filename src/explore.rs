@@ -0,0 +1,254 @@
+//! Ready-made exploration policies.
+//!
+//! The [`Explorer`] first searches for the goal, then returns to the start and afterwards only
+//! visits cells that could still shorten the best known path. It stops as soon as that path is
+//! provably optimal, which keeps the total distance, and therefore the `Score`, low.
+
+use crate::{
+    maze::{Cell, Maze, Pose},
+    solver::{flood, next_step, Assumption},
+    Direction,
+};
+
+/// What the explorer is currently doing
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ExplorePhase {
+    /// Searching for the goal, treating unknown walls as open
+    #[default]
+    ToGoal,
+    /// Heading back to the start cell
+    ToStart,
+    /// Visiting cells that could still lie on a shorter path than the best known one
+    Refine,
+    /// The best known path is optimal, or the goal can not be reached, and the mouse is back at
+    /// the start
+    Done,
+}
+
+/// An exploration policy that decides which neighbouring cell to move to next.
+///
+/// The caller is responsible for sensing and recording the walls around the current pose before
+/// asking for the next move.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Explorer {
+    phase: ExplorePhase,
+}
+
+impl Explorer {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn phase(&self) -> ExplorePhase {
+        self.phase
+    }
+
+    /// Starts over with the search for the goal
+    pub fn reset(&mut self) {
+        self.phase = ExplorePhase::ToGoal;
+    }
+
+    /// The direction of the next cell to move to, or `None` once exploration is finished
+    pub fn next_move(&mut self, maze: &Maze, pose: &Pose) -> Option<Direction> {
+        loop {
+            let targets = match self.phase {
+                ExplorePhase::ToGoal if maze.is_goal(pose.cell) => {
+                    self.phase = ExplorePhase::ToStart;
+                    continue;
+                }
+                ExplorePhase::ToGoal => maze.goals(),
+                ExplorePhase::ToStart if pose.cell == maze.start() => {
+                    // without candidates, e.g. for an enclosed goal, refining can not help
+                    self.phase = if is_optimal(maze) || refine_candidates(maze).is_empty() {
+                        ExplorePhase::Done
+                    } else {
+                        ExplorePhase::Refine
+                    };
+                    continue;
+                }
+                ExplorePhase::ToStart => vec![maze.start()],
+                ExplorePhase::Refine => {
+                    let candidates = refine_candidates(maze);
+                    if candidates.is_empty() || is_optimal(maze) {
+                        self.phase = ExplorePhase::ToStart;
+                        continue;
+                    }
+                    candidates
+                }
+                ExplorePhase::Done => return None,
+            };
+            let distances = flood(maze, &targets, Assumption::Optimistic);
+            let step = next_step(maze, &distances, pose, Assumption::Optimistic);
+            if step.is_none() {
+                self.phase = ExplorePhase::Done;
+            }
+            return step;
+        }
+    }
+}
+
+/// The length in cells of the shortest path from start to goal under the given assumption
+#[must_use]
+pub fn shortest_distance(maze: &Maze, assumption: Assumption) -> Option<u32> {
+    flood(maze, &maze.goals(), assumption)[maze.start()]
+}
+
+/// Returns `true` if the shortest known path is provably optimal, i.e. treating every unknown
+/// wall as open can not produce a shorter one
+#[must_use]
+pub fn is_optimal(maze: &Maze) -> bool {
+    match shortest_distance(maze, Assumption::Pessimistic) {
+        Some(known) => shortest_distance(maze, Assumption::Optimistic) == Some(known),
+        None => false,
+    }
+}
+
+/// The cells with unknown walls that lie on at least one optimistic shortest path from start to
+/// goal. Only exploring these can still shorten the best known path.
+#[must_use]
+pub fn refine_candidates(maze: &Maze) -> Vec<Cell> {
+    let to_goal = flood(maze, &maze.goals(), Assumption::Optimistic);
+    let to_start = flood(maze, &[maze.start()], Assumption::Optimistic);
+    let Some(best) = to_goal[maze.start()] else {
+        return Vec::new();
+    };
+    maze.cells()
+        .filter(|&cell| !maze.is_explored(cell))
+        .filter(
+            |&cell| matches!((to_start[cell], to_goal[cell]), (Some(a), Some(b)) if a + b == best),
+        )
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::WallState;
+
+    /// Runs the explorer in `real`, sensing like the mouse would, and returns the final pose and
+    /// the number of moves
+    fn explore(real: &Maze) -> (Maze, Pose, usize) {
+        let mut maze = Maze::new(real.width(), real.height());
+        let mut explorer = Explorer::new();
+        let mut pose = Pose::start();
+        for moves in 0..1000 {
+            let wall = |direction| real.wall(pose.cell, direction) == WallState::Wall;
+            maze.record_sensors(
+                &pose,
+                wall(pose.heading),
+                wall(pose.heading.left()),
+                wall(pose.heading.right()),
+            );
+            let Some(direction) = explorer.next_move(&maze, &pose) else {
+                assert_eq!(explorer.phase(), ExplorePhase::Done);
+                return (maze, pose, moves);
+            };
+            assert_ne!(real.wall(pose.cell, direction), WallState::Wall);
+            pose.heading = direction;
+            pose.move_forward(1);
+        }
+        panic!("the explorer did not finish");
+    }
+
+    /// A known maze where every wall not listed is open
+    fn known_maze(width: u32, height: u32, walls: &[(u32, u32, Direction)]) -> Maze {
+        let mut maze = Maze::new(width, height);
+        for cell in maze.clone().cells() {
+            for direction in Direction::ALL {
+                maze.set_wall(cell, direction, WallState::Open);
+            }
+        }
+        for &(x, y, direction) in walls {
+            maze.set_wall(Cell::new(x, y), direction, WallState::Wall);
+        }
+        maze
+    }
+
+    /// A 4x4 maze whose 2x2 goal is walled in on all sides
+    fn enclosed_goal() -> Maze {
+        known_maze(
+            4,
+            4,
+            &[
+                (1, 1, Direction::South),
+                (2, 1, Direction::South),
+                (1, 2, Direction::North),
+                (2, 2, Direction::North),
+                (1, 1, Direction::West),
+                (1, 2, Direction::West),
+                (2, 1, Direction::East),
+                (2, 2, Direction::East),
+            ],
+        )
+    }
+
+    #[test]
+    fn open_maze_is_explored_until_optimal() {
+        let real = known_maze(4, 4, &[]);
+        let (maze, pose, _) = explore(&real);
+        assert_eq!(pose.cell, maze.start());
+        assert!(is_optimal(&maze));
+        assert_eq!(shortest_distance(&maze, Assumption::Pessimistic), Some(2));
+    }
+
+    #[test]
+    fn walled_maze_finds_the_detour() {
+        // a wall along x = 0 | 1 for y < 3 forces the path up the west column
+        let real = known_maze(
+            4,
+            4,
+            &[
+                (0, 0, Direction::East),
+                (0, 1, Direction::East),
+                (0, 2, Direction::East),
+            ],
+        );
+        let (maze, pose, _) = explore(&real);
+        assert_eq!(pose.cell, maze.start());
+        assert!(is_optimal(&maze));
+        assert_eq!(shortest_distance(&maze, Assumption::Pessimistic), Some(5));
+    }
+
+    #[test]
+    fn enclosed_goal_stops_exploring() {
+        let (maze, _, _) = explore(&enclosed_goal());
+        assert_eq!(shortest_distance(&maze, Assumption::Optimistic), None);
+        assert!(!is_optimal(&maze));
+    }
+
+    #[test]
+    fn enclosed_goal_at_start_is_done() {
+        let maze = enclosed_goal();
+        let mut explorer = Explorer {
+            phase: ExplorePhase::ToStart,
+        };
+        assert_eq!(explorer.next_move(&maze, &Pose::start()), None);
+        assert_eq!(explorer.phase(), ExplorePhase::Done);
+
+        let mut explorer = Explorer {
+            phase: ExplorePhase::Refine,
+        };
+        assert_eq!(explorer.next_move(&maze, &Pose::start()), None);
+        assert_eq!(explorer.phase(), ExplorePhase::Done);
+    }
+
+    #[test]
+    fn refine_candidates_lie_on_optimistic_shortest_paths() {
+        let mut maze = Maze::new(4, 4);
+        assert!(refine_candidates(&maze).contains(&maze.start()));
+        assert!(!refine_candidates(&maze).contains(&Cell::new(3, 3)));
+        assert!(refine_candidates(&enclosed_goal()).is_empty());
+
+        maze.record_sensors(&Pose::start(), false, true, false);
+        assert!(!refine_candidates(&maze).contains(&maze.start()));
+    }
+
+    #[test]
+    fn optimality_needs_a_known_path() {
+        assert!(!is_optimal(&Maze::new(4, 4)));
+        assert!(is_optimal(&known_maze(4, 4, &[])));
+        assert!(!is_optimal(&enclosed_goal()));
+    }
+}
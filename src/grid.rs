@@ -0,0 +1,130 @@
+//! A rectangular grid holding one value per maze cell
+
+use std::ops::{Index, IndexMut};
+
+use crate::maze::Cell;
+
+/// One value of type `T` per cell of a `width` x `height` maze
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: u32,
+    height: u32,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Creates a grid where every cell holds `value`
+    #[must_use]
+    pub fn new(width: u32, height: u32, value: T) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![value; width as usize * height as usize],
+        }
+    }
+
+    /// Sets every cell to `value`
+    pub fn fill(&mut self, value: T) {
+        self.cells.fill(value);
+    }
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid by calling `f` for every cell
+    #[must_use]
+    pub fn from_fn(width: u32, height: u32, mut f: impl FnMut(Cell) -> T) -> Self {
+        let mut cells = Vec::with_capacity(width as usize * height as usize);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(f(Cell::new(x, y)));
+            }
+        }
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// The number of columns
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The number of rows
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns `true` if the cell lies inside the grid
+    #[must_use]
+    pub fn contains(&self, cell: Cell) -> bool {
+        cell.x < self.width && cell.y < self.height
+    }
+
+    fn index_of(&self, cell: Cell) -> Option<usize> {
+        self.contains(cell)
+            .then(|| cell.y as usize * self.width as usize + cell.x as usize)
+    }
+
+    /// The value of the cell, or `None` if the cell lies outside the grid
+    #[must_use]
+    pub fn get(&self, cell: Cell) -> Option<&T> {
+        self.index_of(cell).map(|i| &self.cells[i])
+    }
+
+    /// The mutable value of the cell, or `None` if the cell lies outside the grid
+    #[must_use]
+    pub fn get_mut(&mut self, cell: Cell) -> Option<&mut T> {
+        self.index_of(cell).map(|i| &mut self.cells[i])
+    }
+
+    /// All cells of the grid, row by row starting at `(0, 0)`
+    pub fn cells(&self) -> impl Iterator<Item = Cell> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Cell::new(x, y)))
+    }
+
+    /// All cells together with their values, row by row starting at `(0, 0)`
+    pub fn iter(&self) -> impl Iterator<Item = (Cell, &T)> {
+        self.cells().zip(self.cells.iter())
+    }
+
+    /// Creates a new grid of the same size by applying `f` to every value
+    #[must_use]
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(&mut f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Cell> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, cell: Cell) -> &Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get(cell).unwrap_or_else(|| {
+            panic!(
+                "cell ({}, {}) is outside the {width}x{height} grid",
+                cell.x, cell.y
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Cell> for Grid<T> {
+    fn index_mut(&mut self, cell: Cell) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(cell).unwrap_or_else(|| {
+            panic!(
+                "cell ({}, {}) is outside the {width}x{height} grid",
+                cell.x, cell.y
+            )
+        })
+    }
+}
@@ -0,0 +1,264 @@
+//! Maps per-cell numbers, like flood distances or visit counts, onto cell colors
+
+use crate::{
+    canvas::{Canvas, DrawTarget},
+    grid::Grid,
+    layers::Layer,
+    maze::Cell,
+    text::TextFormat,
+    CellColor, ResultType,
+};
+
+/// Quantises values onto an ordered palette of colors, from low to high
+#[derive(Clone, Debug, PartialEq)]
+pub struct Heatmap {
+    palette: Vec<CellColor>,
+    range: Option<(f64, f64)>,
+    legend: Option<Cell>,
+}
+
+impl Default for Heatmap {
+    /// A palette going from dark blue over green and yellow to red
+    fn default() -> Self {
+        use CellColor::{
+            Blue, Cyan, DarkBlue, DarkCyan, DarkGreen, DarkRed, DarkYellow, Green, Orange, Red,
+            Yellow,
+        };
+        Self::new(vec![
+            DarkBlue, Blue, DarkCyan, Cyan, DarkGreen, Green, DarkYellow, Yellow, Orange, Red,
+            DarkRed,
+        ])
+    }
+}
+
+impl Heatmap {
+    /// Creates a heatmap with the given palette, ordered from the lowest to the highest value.
+    /// The range is taken from the painted values.
+    ///
+    /// # Panics
+    /// if the palette is empty
+    #[must_use]
+    pub fn new(palette: Vec<CellColor>) -> Self {
+        assert!(!palette.is_empty(), "the palette needs at least one color");
+        Self {
+            palette,
+            range: None,
+            legend: None,
+        }
+    }
+
+    /// Uses a fixed range instead of the smallest and largest painted value. Values outside the
+    /// range get the first or last color.
+    #[must_use]
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    /// Writes a legend when painting: one cell per palette color, going north from `origin`,
+    /// holding the lowest value of that color as text. The legend is drawn on top of the heatmap.
+    #[must_use]
+    pub fn with_legend(mut self, origin: Cell) -> Self {
+        self.legend = Some(origin);
+        self
+    }
+
+    #[must_use]
+    pub fn palette(&self) -> &[CellColor] {
+        &self.palette
+    }
+
+    /// The range used for the given values, `None` if there are no values and no fixed range.
+    /// `NaN` values are ignored.
+    #[must_use]
+    pub fn range<T: Copy + Into<f64>>(&self, values: &Grid<Option<T>>) -> Option<(f64, f64)> {
+        self.range.or_else(|| {
+            values
+                .iter()
+                .filter_map(|(_, value)| value.map(Into::into))
+                .filter(|value: &f64| !value.is_nan())
+                .fold(None, |range, v| match range {
+                    None => Some((v, v)),
+                    Some((min, max)) => Some((f64::min(min, v), f64::max(max, v))),
+                })
+        })
+    }
+
+    /// The color for a value within the range. Every value gets the first color if the range is
+    /// empty, and so does `NaN`.
+    #[must_use]
+    pub fn color(&self, value: f64, (min, max): (f64, f64)) -> CellColor {
+        let last = self.palette.len() - 1;
+        let position = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.0
+        };
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let index = ((position * self.palette.len() as f64).floor().max(0.0) as usize).min(last);
+        self.palette[index]
+    }
+
+    /// The color of every cell, `None` for cells without a value
+    #[must_use]
+    pub fn colors<T: Copy + Into<f64>>(&self, values: &Grid<Option<T>>) -> Grid<Option<CellColor>> {
+        let Some(range) = self.range(values) else {
+            return values.map(|_| None);
+        };
+        values.map(|value| value.map(|value| self.color(value.into(), range)))
+    }
+
+    /// The legend entries: every palette color with the lowest value that maps to it, fitted
+    /// into a cell
+    #[must_use]
+    pub fn legend(&self, (min, max): (f64, f64)) -> Vec<(CellColor, String)> {
+        #[allow(clippy::cast_precision_loss)]
+        let step = (max - min) / self.palette.len() as f64;
+        let format = TextFormat {
+            precision: usize::from(step < 1.0 && step > 0.0),
+            ..TextFormat::default()
+        };
+        (0..)
+            .zip(&self.palette)
+            .map(|(i, &color)| (color, format.float(min + step * f64::from(i))))
+            .collect()
+    }
+
+    /// Paints the values through the canvas. Cells without a value have their color cleared.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn paint<T: Copy + Into<f64>, D: DrawTarget>(
+        &self,
+        canvas: &mut Canvas<D>,
+        values: &Grid<Option<T>>,
+    ) -> ResultType<()> {
+        for (cell, color) in self.colors(values).iter() {
+            match color {
+                Some(color) => try_api!(canvas.set_color(cell, *color)),
+                None => try_api!(canvas.clear_color(cell)),
+            }
+        }
+        if let (Some(origin), Some(range)) = (self.legend, self.range(values)) {
+            for (cell, (color, text)) in legend_cells(origin).zip(self.legend(range)) {
+                try_api!(canvas.set_color(cell, color));
+                try_api!(canvas.set_text(cell, &text));
+            }
+        }
+        return_result!(());
+    }
+
+    /// Paints the values and the legend into a layer of a
+    /// [`LayerStack`](crate::layers::LayerStack). Cells without a value are left transparent.
+    pub fn paint_into<T: Copy + Into<f64>>(&self, layer: &mut Layer, values: &Grid<Option<T>>) {
+        for (cell, color) in self.colors(values).iter() {
+            match color {
+                Some(color) => layer.set_color(cell, *color),
+                None => layer.clear_color(cell),
+            }
+        }
+        if let (Some(origin), Some(range)) = (self.legend, self.range(values)) {
+            for (cell, (color, text)) in legend_cells(origin).zip(self.legend(range)) {
+                layer.set_color(cell, color);
+                layer.set_text(cell, &text);
+            }
+        }
+    }
+}
+
+/// The cells of a legend, going north from the origin
+fn legend_cells(origin: Cell) -> impl Iterator<Item = Cell> {
+    (0..).map_while(move |i| Some(Cell::new(origin.x, origin.y.checked_add(i)?)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::text::MAX_TEXT_LEN;
+
+    fn palette() -> Heatmap {
+        Heatmap::new(vec![CellColor::Blue, CellColor::Green, CellColor::Red])
+    }
+
+    #[test]
+    fn values_are_spread_evenly_over_the_palette() {
+        let heatmap = palette();
+        let colors: Vec<CellColor> = [0.0, 3.2, 3.4, 6.6, 6.7, 10.0]
+            .into_iter()
+            .map(|value| heatmap.color(value, (0.0, 10.0)))
+            .collect();
+        use CellColor::{Blue, Green, Red};
+        assert_eq!(colors, [Blue, Blue, Green, Green, Red, Red]);
+        assert_eq!(heatmap.color(-5.0, (0.0, 10.0)), Blue);
+        assert_eq!(heatmap.color(15.0, (0.0, 10.0)), Red);
+    }
+
+    #[test]
+    fn the_range_comes_from_the_values_unless_fixed() {
+        let mut values = Grid::new(3, 1, None);
+        assert_eq!(palette().range::<u32>(&values), None);
+        values[Cell::new(0, 0)] = Some(4);
+        values[Cell::new(2, 0)] = Some(9);
+        assert_eq!(palette().range(&values), Some((4.0, 9.0)));
+        assert_eq!(
+            palette().with_range(0.0, 20.0).range(&values),
+            Some((0.0, 20.0))
+        );
+        assert_eq!(
+            palette()
+                .colors(&values)
+                .iter()
+                .map(|(_, c)| *c)
+                .collect::<Vec<_>>(),
+            [Some(CellColor::Blue), None, Some(CellColor::Red)]
+        );
+    }
+
+    #[test]
+    fn an_empty_range_uses_the_first_color() {
+        let values = Grid::new(2, 1, Some(5));
+        assert_eq!(palette().range(&values), Some((5.0, 5.0)));
+        assert!(palette()
+            .colors(&values)
+            .iter()
+            .all(|(_, color)| *color == Some(CellColor::Blue)));
+    }
+
+    #[test]
+    fn nan_is_ignored_for_the_range_and_gets_the_first_color() {
+        let mut values = Grid::new(3, 1, Some(f64::NAN));
+        assert_eq!(palette().range(&values), None);
+        values[Cell::new(1, 0)] = Some(1.0);
+        values[Cell::new(2, 0)] = Some(2.0);
+        assert_eq!(palette().range(&values), Some((1.0, 2.0)));
+        assert_eq!(
+            palette().colors(&values)[Cell::new(0, 0)],
+            Some(CellColor::Blue)
+        );
+    }
+
+    #[test]
+    fn legend_holds_the_lowest_value_of_every_color() {
+        let heatmap = palette();
+        let texts = |range| -> Vec<String> {
+            heatmap
+                .legend(range)
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect()
+        };
+        assert_eq!(texts((0.0, 30.0)), ["0", "10", "20"]);
+        assert_eq!(texts((0.0, 1.5)), ["0.0", "0.5", "1.0"]);
+        assert_eq!(texts((2.0, 2.0)), ["2", "2", "2"]);
+        let large = texts((0.0, 3e12));
+        assert_eq!(large[1], "1000000.0M");
+        assert!(large.iter().all(|text| text.len() <= MAX_TEXT_LEN));
+        assert_eq!(heatmap.legend((0.0, 30.0))[2].0, CellColor::Red,);
+    }
+}
@@ -0,0 +1,287 @@
+//! Named overlay layers for debugging visuals.
+//!
+//! Every part of a bot draws into its own [`Layer`]. The [`LayerStack`] composites them by
+//! priority and sends the top-most color and text of every cell to mms through a [`Canvas`], so
+//! hiding a layer brings back whatever is below it.
+
+use crate::{
+    canvas::{Canvas, DrawTarget},
+    grid::Grid,
+    maze::Cell,
+    text::{CellText, TextFormat},
+    CellColor, ResultType,
+};
+
+/// The colors and texts drawn by one part of a bot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layer {
+    name: String,
+    priority: i32,
+    visible: bool,
+    colors: Grid<Option<CellColor>>,
+    texts: Grid<Option<String>>,
+}
+
+impl Layer {
+    fn new(name: &str, priority: i32, width: u32, height: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            priority,
+            visible: true,
+            colors: Grid::new(width, height, None),
+            texts: Grid::new(width, height, None),
+        }
+    }
+
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Layers with a higher priority are drawn on top
+    #[must_use]
+    pub fn priority(&self) -> i32 {
+        self.priority
+    }
+
+    #[must_use]
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// The color of the cell in this layer, `None` if the layer is transparent there
+    #[must_use]
+    pub fn color(&self, cell: Cell) -> Option<CellColor> {
+        self.colors.get(cell).copied().flatten()
+    }
+
+    /// The text of the cell in this layer, `None` if the layer is transparent there
+    #[must_use]
+    pub fn text(&self, cell: Cell) -> Option<&str> {
+        self.texts.get(cell).and_then(Option::as_deref)
+    }
+
+    pub fn set_color(&mut self, cell: Cell, color: CellColor) {
+        if let Some(c) = self.colors.get_mut(cell) {
+            *c = Some(color);
+        }
+    }
+
+    /// Makes the color of the cell transparent
+    pub fn clear_color(&mut self, cell: Cell) {
+        if let Some(c) = self.colors.get_mut(cell) {
+            *c = None;
+        }
+    }
+
+    pub fn set_text(&mut self, cell: Cell, text: &str) {
+        if let Some(t) = self.texts.get_mut(cell) {
+            *t = Some(text.to_string());
+        }
+    }
+
+    /// Writes every value of the grid into its cell, formatted to fit. Values that format to an
+    /// empty text, like `None`, make the cell transparent.
+    pub fn set_text_grid<T: CellText>(&mut self, values: &Grid<T>, format: &TextFormat) {
+        for (cell, value) in values.iter() {
+            let text = value.cell_text(format);
+            if text.is_empty() {
+                self.clear_text(cell);
+            } else {
+                self.set_text(cell, &text);
+            }
+        }
+    }
+
+    /// Makes the text of the cell transparent
+    pub fn clear_text(&mut self, cell: Cell) {
+        if let Some(t) = self.texts.get_mut(cell) {
+            *t = None;
+        }
+    }
+
+    /// Makes the whole layer transparent
+    pub fn clear(&mut self) {
+        self.colors.fill(None);
+        self.texts.fill(None);
+    }
+}
+
+/// Layers sorted by priority, composited into the simulator display.
+///
+/// Once presented, the stack owns the colors and texts of the whole maze: cells where no visible
+/// layer has a value are cleared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayerStack {
+    width: u32,
+    height: u32,
+    layers: Vec<Layer>,
+}
+
+impl LayerStack {
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Returns the layer with the given name, creating an empty one with the given priority if
+    /// it does not exist yet. Layers with equal priority are drawn in the order they were added.
+    pub fn layer(&mut self, name: &str, priority: i32) -> &mut Layer {
+        let index = match self.layers.iter().position(|layer| layer.name == name) {
+            Some(index) => index,
+            None => {
+                let index = self
+                    .layers
+                    .iter()
+                    .position(|layer| layer.priority > priority)
+                    .unwrap_or(self.layers.len());
+                let layer = Layer::new(name, priority, self.width, self.height);
+                self.layers.insert(index, layer);
+                index
+            }
+        };
+        &mut self.layers[index]
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&Layer> {
+        self.layers.iter().find(|layer| layer.name == name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Layer> {
+        self.layers.iter_mut().find(|layer| layer.name == name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Layer> {
+        let index = self.layers.iter().position(|layer| layer.name == name)?;
+        Some(self.layers.remove(index))
+    }
+
+    /// Shows or hides the layer. Returns `false` if there is no layer with that name.
+    pub fn set_visible(&mut self, name: &str, visible: bool) -> bool {
+        self.get_mut(name)
+            .map(|layer| layer.visible = visible)
+            .is_some()
+    }
+
+    /// All layers from the bottom to the top
+    pub fn layers(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter()
+    }
+
+    /// The top-most visible color of the cell
+    #[must_use]
+    pub fn color(&self, cell: Cell) -> Option<CellColor> {
+        self.visible_from_top().find_map(|layer| layer.color(cell))
+    }
+
+    /// The top-most visible text of the cell
+    #[must_use]
+    pub fn text(&self, cell: Cell) -> Option<&str> {
+        self.visible_from_top().find_map(|layer| layer.text(cell))
+    }
+
+    fn visible_from_top(&self) -> impl Iterator<Item = &Layer> {
+        self.layers.iter().rev().filter(|layer| layer.visible)
+    }
+
+    /// Sends the composited colors and texts to mms. Only changed cells are updated.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn present<D: DrawTarget>(&self, canvas: &mut Canvas<D>) -> ResultType<()> {
+        let mut frame = canvas.frame();
+        for cell in frame.cells().collect::<Vec<_>>() {
+            frame[cell].color = self.color(cell);
+            frame[cell].text = self.text(cell).unwrap_or_default().to_string();
+        }
+        canvas.present(&frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::testing::{canvas, sent};
+
+    const CELL: Cell = Cell::new(1, 0);
+
+    fn names(stack: &LayerStack) -> Vec<&str> {
+        stack.layers().map(Layer::name).collect()
+    }
+
+    #[test]
+    fn layers_are_sorted_by_priority_then_insertion() {
+        let mut stack = LayerStack::new(2, 1);
+        stack.layer("path", 5);
+        stack.layer("heat", 0);
+        stack.layer("marks", 5);
+        stack.layer("debug", 9);
+        assert_eq!(stack.layer("heat", 100).priority(), 0);
+        assert_eq!(names(&stack), ["heat", "path", "marks", "debug"]);
+
+        assert_eq!(stack.remove("path").map(|layer| layer.priority()), Some(5));
+        assert!(stack.remove("path").is_none());
+        assert_eq!(names(&stack), ["heat", "marks", "debug"]);
+    }
+
+    #[test]
+    fn the_top_most_visible_value_wins() {
+        let mut stack = LayerStack::new(2, 1);
+        stack.layer("heat", 0).set_color(CELL, CellColor::Blue);
+        stack.layer("heat", 0).set_text(CELL, "7");
+        stack.layer("path", 1).set_color(CELL, CellColor::Red);
+        assert_eq!(stack.color(CELL), Some(CellColor::Red));
+        // The path has no text in the cell, so the one below shows through
+        assert_eq!(stack.text(CELL), Some("7"));
+
+        assert!(stack.set_visible("path", false));
+        assert_eq!(stack.color(CELL), Some(CellColor::Blue));
+        assert!(!stack.set_visible("missing", false));
+
+        stack.get_mut("heat").unwrap().clear_color(CELL);
+        assert_eq!(stack.color(CELL), None);
+        assert!(stack.set_visible("path", true));
+        assert_eq!(stack.color(CELL), Some(CellColor::Red));
+        assert_eq!(stack.color(Cell::new(9, 9)), None);
+    }
+
+    #[test]
+    fn text_grids_leave_empty_values_transparent() {
+        let mut stack = LayerStack::new(2, 1);
+        stack.layer("below", 0).set_text(Cell::new(0, 0), "b");
+        let values = Grid::from_fn(2, 1, |cell| (cell.x == 1).then_some(42));
+        stack
+            .layer("numbers", 1)
+            .set_text_grid(&values, &TextFormat::default());
+        assert_eq!(stack.text(Cell::new(0, 0)), Some("b"));
+        assert_eq!(stack.text(CELL), Some("42"));
+
+        stack.get_mut("numbers").unwrap().clear();
+        assert_eq!(stack.text(CELL), None);
+    }
+
+    #[test]
+    fn present_sends_only_changed_cells() {
+        let mut canvas = canvas(2, 1);
+        let mut stack = LayerStack::new(2, 1);
+        stack.layer("heat", 0).set_color(CELL, CellColor::Blue);
+        stack.layer("path", 1).set_color(CELL, CellColor::Red);
+        stack.layer("path", 1).set_text(CELL, "p");
+        unwrap_api!(stack.present(&mut canvas));
+        assert_eq!(sent(&mut canvas), ["setColor 1 0 r", "setText 1 0 p"]);
+
+        unwrap_api!(stack.present(&mut canvas));
+        assert!(sent(&mut canvas).is_empty());
+
+        stack.set_visible("path", false);
+        unwrap_api!(stack.present(&mut canvas));
+        assert_eq!(sent(&mut canvas), ["setColor 1 0 b", "clearText 1 0"]);
+    }
+}
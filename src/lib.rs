@@ -1,13 +1,20 @@
 //! Rust Api for mms (micromouse simulator)
 
 use std::{
-    io::{stdin, stdout, BufRead, StdinLock, Write},
     num::{NonZeroU32, ParseFloatError, ParseIntError},
     str::FromStr,
+    sync::{Mutex, MutexGuard, PoisonError},
 };
 
-#[cfg(feature = "c_api")]
-mod c_api;
+use connection::{Connection, Stdio};
+
+pub mod explore;
+pub mod grid;
+pub mod maze;
+pub mod score;
+pub mod solver;
+pub mod store;
+pub mod text;
 
 #[derive(thiserror::Error, Debug)]
 pub enum MmsError {
@@ -25,9 +32,21 @@ pub enum MmsError {
     InvalidColorString(String),
     #[error("InvalidDirectionString: {0}")]
     InvalidDirectionString(String),
+    #[error("InvalidMazeFile: {0}")]
+    InvalidMazeFile(String),
+    #[error("InvalidMazeSize: {0}")]
+    InvalidMazeSize(String),
 }
 
 /// Which stat to query
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, from_py_object)
+)]
 pub enum StatQuery {
     TotalDistance,
     TotalTurns,
@@ -62,7 +81,21 @@ impl FromStr for StatQuery {
 }
 
 impl StatQuery {
-    fn get_string(&self) -> &'static str {
+    /// All stats, in the order they are listed in the mms documentation
+    pub const ALL: [StatQuery; 10] = [
+        StatQuery::TotalDistance,
+        StatQuery::TotalTurns,
+        StatQuery::BestRunDistance,
+        StatQuery::BestRunTurns,
+        StatQuery::CurrentRunDistance,
+        StatQuery::CurrentRunTurns,
+        StatQuery::TotalEffectiveDistance,
+        StatQuery::BestRunEffectiveDistance,
+        StatQuery::CurrentRunEffectiveDistance,
+        StatQuery::Score,
+    ];
+
+    pub(crate) fn get_string(&self) -> &'static str {
         match self {
             StatQuery::TotalDistance => "total-distance",
             StatQuery::TotalTurns => "total-turns",
@@ -78,7 +111,28 @@ impl StatQuery {
     }
 }
 
+/// Implements `TryFrom<i32>` for enums with an `ALL` list, e.g. for values passed from C. Fails
+/// with the value if no variant has it.
+macro_rules! try_from_i32 {
+    ($($t: ty),*) => {$(
+        impl TryFrom<i32> for $t {
+            type Error = i32;
+
+            fn try_from(value: i32) -> Result<Self, Self::Error> {
+                Self::ALL
+                    .into_iter()
+                    .find(|&variant| variant as i32 == value)
+                    .ok_or(value)
+            }
+        }
+    )*};
+}
+
+try_from_i32!(StatQuery, Direction, CellColor);
+
 /// The stat that was requested
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stat {
     TotalDistance(i32),
     TotalTurns(i32),
@@ -92,7 +146,76 @@ pub enum Stat {
     Score(f32),
 }
 
+impl Stat {
+    /// The query this stat answers
+    #[must_use]
+    pub fn query(&self) -> StatQuery {
+        match self {
+            Stat::TotalDistance(_) => StatQuery::TotalDistance,
+            Stat::TotalTurns(_) => StatQuery::TotalTurns,
+            Stat::BestRunDistance(_) => StatQuery::BestRunDistance,
+            Stat::BestRunTurns(_) => StatQuery::BestRunTurns,
+            Stat::CurrentRunDistance(_) => StatQuery::CurrentRunDistance,
+            Stat::CurrentRunTurns(_) => StatQuery::CurrentRunTurns,
+            Stat::TotalEffectiveDistance(_) => StatQuery::TotalEffectiveDistance,
+            Stat::BestRunEffectiveDistance(_) => StatQuery::BestRunEffectiveDistance,
+            Stat::CurrentRunEffectiveDistance(_) => StatQuery::CurrentRunEffectiveDistance,
+            Stat::Score(_) => StatQuery::Score,
+        }
+    }
+
+    /// The value of an integer stat, or `None` for float stats and if no value exists yet
+    #[must_use]
+    pub fn int_value(&self) -> Option<i32> {
+        match *self {
+            Stat::TotalDistance(i)
+            | Stat::TotalTurns(i)
+            | Stat::BestRunDistance(i)
+            | Stat::BestRunTurns(i)
+            | Stat::CurrentRunDistance(i)
+            | Stat::CurrentRunTurns(i) => Some(i).filter(|&i| i != -1),
+            Stat::TotalEffectiveDistance(_)
+            | Stat::BestRunEffectiveDistance(_)
+            | Stat::CurrentRunEffectiveDistance(_)
+            | Stat::Score(_) => None,
+        }
+    }
+
+    /// The value of a float stat, or `None` for integer stats and if no value exists yet
+    #[must_use]
+    pub fn float_value(&self) -> Option<f32> {
+        match *self {
+            Stat::TotalEffectiveDistance(f)
+            | Stat::BestRunEffectiveDistance(f)
+            | Stat::CurrentRunEffectiveDistance(f)
+            | Stat::Score(f) => Some(f).filter(|&f| f != -1.0),
+            Stat::TotalDistance(_)
+            | Stat::TotalTurns(_)
+            | Stat::BestRunDistance(_)
+            | Stat::BestRunTurns(_)
+            | Stat::CurrentRunDistance(_)
+            | Stat::CurrentRunTurns(_) => None,
+        }
+    }
+
+    /// The value of any stat, or `None` if no value exists yet
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn value(&self) -> Option<f32> {
+        self.float_value()
+            .or_else(|| self.int_value().map(|i| i as f32))
+    }
+}
+
 /// The direction for the wall
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, from_py_object)
+)]
 pub enum Direction {
     North,
     East,
@@ -116,7 +239,37 @@ impl FromStr for Direction {
 }
 
 impl Direction {
-    fn get_string(&self) -> char {
+    /// All directions, clockwise starting at `North`
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The direction after turning ninety degrees to the left
+    #[must_use]
+    pub fn left(self) -> Self {
+        Direction::ALL[(self.index() + 3) % 4]
+    }
+
+    /// The direction after turning ninety degrees to the right
+    #[must_use]
+    pub fn right(self) -> Self {
+        Direction::ALL[(self.index() + 1) % 4]
+    }
+
+    /// The direction pointing the other way
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Direction::ALL[(self.index() + 2) % 4]
+    }
+
+    pub(crate) fn index(self) -> usize {
+        self as usize
+    }
+
+    pub(crate) fn get_string(&self) -> char {
         use Direction::{East, North, South, West};
         match self {
             North => 'n',
@@ -128,6 +281,14 @@ impl Direction {
 }
 
 /// The cell color
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, from_py_object)
+)]
 pub enum CellColor {
     Black,
     Blue,
@@ -176,7 +337,26 @@ impl FromStr for CellColor {
 }
 
 impl CellColor {
-    fn get_char(&self) -> char {
+    /// All colors, in the order they are listed in the mms documentation
+    pub const ALL: [CellColor; 15] = [
+        CellColor::Black,
+        CellColor::Blue,
+        CellColor::Gray,
+        CellColor::Cyan,
+        CellColor::Green,
+        CellColor::Orange,
+        CellColor::Red,
+        CellColor::White,
+        CellColor::Yellow,
+        CellColor::DarkBlue,
+        CellColor::DarkCyan,
+        CellColor::DarkGray,
+        CellColor::DarkGreen,
+        CellColor::DarkRed,
+        CellColor::DarkYellow,
+    ];
+
+    pub(crate) fn get_char(&self) -> char {
         use CellColor::{
             Black, Blue, Cyan, DarkBlue, DarkCyan, DarkGray, DarkGreen, DarkRed, DarkYellow, Gray,
             Green, Orange, Red, White, Yellow,
@@ -201,30 +381,36 @@ impl CellColor {
     }
 }
 
-/// The main wrapper around the mms api. Holds locks to `stdin` and `stdout` to allow for fast and
-/// exclusive access for the api.
+/// The main wrapper around the mms api. Talks to mms over `stdin` and `stdout` through a global
+/// [`Connection`], use a [`Connection`] directly for other transports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct MmsApi;
 
+static STDIO: Mutex<Connection<Stdio>> = Mutex::new(Connection::new(Stdio));
+
+fn stdio() -> MutexGuard<'static, Connection<Stdio>> {
+    STDIO.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// The return type of all api calls. Plain values when `use_panics` is enabled.
 #[cfg(not(feature = "use_panics"))]
-type ResultType<T> = Result<T, MmsError>;
+pub type ResultType<T> = Result<T, MmsError>;
 
+/// The return type of all api calls. Plain values when `use_panics` is enabled.
 #[cfg(feature = "use_panics")]
-type ResultType<T> = T;
+pub type ResultType<T> = T;
 
+/// Turns the result of a [`Connection`] call into the return type of the api
 #[cfg(not(feature = "use_panics"))]
-macro_rules! writeln_and_flush {
-    ($dst:expr, $($arg:tt)*) => {
-        writeln!($dst, $($arg)*)?;
-        $dst.flush()?;
-    };
+fn api<T>(result: Result<T, MmsError>) -> ResultType<T> {
+    result
 }
 
+/// Turns the result of a [`Connection`] call into the return type of the api, panicking on
+/// errors
 #[cfg(feature = "use_panics")]
-macro_rules! writeln_and_flush {
-    ($dst:expr, $($arg:tt)*) => {
-        writeln!($dst, $($arg)*).unwrap();
-        $dst.flush().unwrap();
-    };
+fn api<T>(result: Result<T, MmsError>) -> ResultType<T> {
+    result.unwrap()
 }
 
 #[cfg(not(feature = "use_panics"))]
@@ -256,11 +442,51 @@ macro_rules! return_result {
     };
 }
 
-macro_rules! ack {
-    ($cin: expr) => {
-        return MmsApi::read_ack(&mut $cin);
+#[cfg(not(feature = "use_panics"))]
+macro_rules! try_api {
+    ($e: expr) => {
+        $e?
+    };
+}
+#[cfg(feature = "use_panics")]
+macro_rules! try_api {
+    ($e: expr) => {{
+        let value = $e;
+        value
+    }};
+}
+
+/// Unwraps the result of an api call in tests, with or without `use_panics`
+#[cfg(all(test, not(feature = "use_panics")))]
+macro_rules! unwrap_api {
+    ($e: expr) => {
+        $e.unwrap()
     };
 }
+#[cfg(all(test, feature = "use_panics"))]
+macro_rules! unwrap_api {
+    ($e: expr) => {{
+        let value = $e;
+        value
+    }};
+}
+
+pub mod bot;
+#[cfg(feature = "c_api_internal")]
+mod c_api;
+pub mod canvas;
+pub mod connection;
+pub mod consistency;
+pub mod heatmap;
+pub mod layers;
+pub mod overlay;
+pub mod phases;
+pub mod protocol;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod stats;
 
 impl MmsApi {
     /// Returns the width of the maze
@@ -273,12 +499,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn maze_width() -> ResultType<i32> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "mazeWidth");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(handle_result!(response.trim().parse()));
+        api(stdio().maze_width())
     }
 
     /// Returns the height of the maze
@@ -291,12 +512,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn maze_height() -> ResultType<i32> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "mazeHeight");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(handle_result!(response.trim().parse()));
+        api(stdio().maze_height())
     }
 
     /// Returns `true` if there is a wall in front of the robot, else `false`
@@ -309,12 +525,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn wall_front() -> ResultType<bool> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "wallFront");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(response.trim() == "true");
+        api(stdio().wall_front())
     }
 
     /// Returns `true` if there is a wall to the right of the robot, else `false`
@@ -327,12 +538,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn wall_right() -> ResultType<bool> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "wallRight");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(response.trim() == "true");
+        api(stdio().wall_right())
     }
 
     /// Returns `true` if there is a wall to the left of the robot, else `false`
@@ -345,12 +551,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn wall_left() -> ResultType<bool> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "wallLeft");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(response.trim() == "true");
+        api(stdio().wall_left())
     }
 
     /// Move the robot forward the specified number of cells
@@ -367,14 +568,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn move_forward(distance: Option<NonZeroU32>) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(
-            cout,
-            "moveForward {}",
-            distance.map_or_else(String::new, |d| d.to_string())
-        );
-        ack!(cin);
+        api(stdio().move_forward(distance))
     }
 
     /// Turn the robot ninety degrees to the right
@@ -388,10 +582,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn turn_right() -> ResultType<()> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "turnRight");
-        ack!(cin);
+        api(stdio().turn_right())
     }
 
     /// Turn the robot ninety degrees to the left
@@ -405,10 +596,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn turn_left() -> ResultType<()> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "turnLeft");
-        ack!(cin);
+        api(stdio().turn_left())
     }
 
     /// Display a wall at the given position
@@ -426,9 +614,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn set_wall(x: u32, y: u32, direction: &Direction) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "setWall {x} {y} {}", direction.get_string());
-        return_result!(());
+        api(stdio().set_wall(x, y, direction))
     }
 
     /// Clear the wall at the given position
@@ -446,9 +632,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn clear_wall(x: u32, y: u32, direction: &Direction) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "clearWall {x} {y} {}", direction.get_string());
-        return_result!(());
+        api(stdio().clear_wall(x, y, direction))
     }
 
     /// Set the color of the cell at the given position
@@ -466,9 +650,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn set_color(x: u32, y: u32, color: &CellColor) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "setColor {x} {y} {}", color.get_char());
-        return_result!(());
+        api(stdio().set_color(x, y, color))
     }
 
     /// Clear the color of the cell at the given position
@@ -485,9 +667,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn clear_color(x: u32, y: u32) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "clearColor {x} {y}");
-        return_result!(());
+        api(stdio().clear_color(x, y))
     }
 
     /// Clear the color of all cells
@@ -500,9 +680,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn clear_all_color() -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "clearAllColor");
-        return_result!(());
+        api(stdio().clear_all_color())
     }
 
     /// Set the text of the cell at the given position
@@ -520,9 +698,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn set_text(x: u32, y: u32, text: &str) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "setText {x} {y} {text}");
-        return_result!(());
+        api(stdio().set_text(x, y, text))
     }
 
     /// Clear the text of the cell at the given position
@@ -539,9 +715,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn clear_text(x: u32, y: u32) -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "clearText {x} {y}");
-        return_result!(());
+        api(stdio().clear_text(x, y))
     }
 
     /// Clear the text of all cells
@@ -554,9 +728,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn clear_all_text() -> ResultType<()> {
-        let mut cout = stdout().lock();
-        writeln_and_flush!(cout, "clearAllText");
-        return_result!(());
+        api(stdio().clear_all_text())
     }
 
     /// Returns `true` if the reset button was pressed, else `false`
@@ -569,12 +741,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn was_reset() -> ResultType<bool> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "wasReset");
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        return_result!(response.trim() == "true");
+        api(stdio().was_reset())
     }
 
     /// Allow the mouse to be moved back to the start of the maze
@@ -588,10 +755,7 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn ack_reset() -> ResultType<()> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "ackReset");
-        ack!(cin);
+        api(stdio().ack_reset())
     }
 
     /// The value of the stat, or `-1` if no value exists yet.
@@ -604,46 +768,28 @@ impl MmsApi {
     /// this panics when `use_panics` is disabled
     #[cfg_attr(feature = "use_panics", must_use)]
     pub fn get_stat(query: &StatQuery) -> ResultType<Stat> {
-        let mut cout = stdout().lock();
-        let mut cin = stdin().lock();
-        writeln_and_flush!(cout, "{}", query.get_string());
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        let response = response.trim();
-        let result = match query {
-            StatQuery::TotalDistance => Stat::TotalDistance(handle_result!(response.parse())),
-            StatQuery::TotalTurns => Stat::TotalTurns(handle_result!(response.parse())),
-            StatQuery::BestRunDistance => Stat::BestRunDistance(handle_result!(response.parse())),
-            StatQuery::BestRunTurns => Stat::BestRunTurns(handle_result!(response.parse())),
-            StatQuery::CurrentRunDistance => {
-                Stat::CurrentRunDistance(handle_result!(response.parse()))
-            }
-            StatQuery::CurrentRunTurns => Stat::CurrentRunTurns(handle_result!(response.parse())),
-            StatQuery::TotalEffectiveDistance => {
-                Stat::TotalEffectiveDistance(handle_result!(response.parse()))
-            }
-            StatQuery::BestRunEffectiveDistance => {
-                Stat::BestRunEffectiveDistance(handle_result!(response.parse()))
-            }
-            StatQuery::CurrentRunEffectiveDistance => {
-                Stat::CurrentRunEffectiveDistance(handle_result!(response.parse()))
-            }
-            StatQuery::Score => Stat::Score(handle_result!(response.parse())),
-        };
-        return_result!(result);
-    }
-
-    fn read_ack(cin: &mut StdinLock) -> ResultType<()> {
-        let mut response = String::new();
-        handle_result!(cin.read_line(&mut response));
-        let ack = response.trim();
-        #[cfg(not(feature = "use_panics"))]
-        if ack == "ack" {
-            Ok(())
-        } else {
-            Err(MmsError::InvalidAck(response))
-        }
-        #[cfg(feature = "use_panics")]
-        assert!(ack == "ack", "{response}");
+        api(stdio().get_stat(query))
+    }
+
+    /// The stats computed locally from the commands sent so far, see
+    /// [`StatsTracker`](stats::StatsTracker). The score is not tracked.
+    #[must_use]
+    pub fn tracked_stats() -> stats::RunStats {
+        stdio().tracker().stats()
+    }
+
+    /// Compares the locally tracked stats with the ones reported by mms. Differences point to
+    /// crashes or a misunderstanding of how mms counts.
+    ///
+    /// # Errors
+    /// `IoError`
+    /// `ParseIntError`
+    /// `ParseFloatError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    #[cfg_attr(feature = "use_panics", must_use)]
+    pub fn check_stats() -> ResultType<Vec<stats::StatDiscrepancy>> {
+        let reported = try_api!(stats::RunStats::fetch());
+        return_result!(stdio().tracker().check(&reported));
     }
 }
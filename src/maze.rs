@@ -0,0 +1,426 @@
+//! What the mouse knows about the maze: walls, visited cells and its own pose
+
+use crate::{grid::Grid, Direction};
+
+/// The position of a cell. `(0, 0)` is the bottom left corner, `y` grows towards the north.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cell {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Cell {
+    #[must_use]
+    pub const fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+
+    /// The adjacent cell in the given direction, or `None` if it would have a negative coordinate
+    #[must_use]
+    pub fn step(self, direction: Direction) -> Option<Self> {
+        let Self { x, y } = self;
+        match direction {
+            Direction::North => y.checked_add(1).map(|y| Self { x, y }),
+            Direction::East => x.checked_add(1).map(|x| Self { x, y }),
+            Direction::South => y.checked_sub(1).map(|y| Self { x, y }),
+            Direction::West => x.checked_sub(1).map(|x| Self { x, y }),
+        }
+    }
+}
+
+/// What is known about a single wall
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, from_py_object)
+)]
+pub enum WallState {
+    /// The wall was never sensed
+    #[default]
+    Unknown,
+    /// There is no wall
+    Open,
+    /// There is a wall
+    Wall,
+}
+
+impl WallState {
+    /// Returns `true` if the wall is known, either as open or as wall
+    #[must_use]
+    pub fn is_known(self) -> bool {
+        self != WallState::Unknown
+    }
+}
+
+impl From<bool> for WallState {
+    fn from(present: bool) -> Self {
+        if present {
+            WallState::Wall
+        } else {
+            WallState::Open
+        }
+    }
+}
+
+/// The knowledge the mouse has collected about the maze.
+///
+/// Every wall is stored on both of its sides, so recording a wall for one cell also records it
+/// for the neighbouring cell. The outer boundary is always known to be a wall.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Maze {
+    walls: Grid<[WallState; 4]>,
+    visited: Grid<bool>,
+}
+
+impl Maze {
+    /// Creates an unexplored maze where only the outer boundary is known
+    #[must_use]
+    pub fn new(width: u32, height: u32) -> Self {
+        let walls = Grid::from_fn(width, height, |cell| {
+            let mut walls = [WallState::Unknown; 4];
+            for direction in Direction::ALL {
+                if !cell
+                    .step(direction)
+                    .is_some_and(|n| n.x < width && n.y < height)
+                {
+                    walls[direction.index()] = WallState::Wall;
+                }
+            }
+            walls
+        });
+        Self {
+            walls,
+            visited: Grid::new(width, height, false),
+        }
+    }
+
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.walls.width()
+    }
+
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.walls.height()
+    }
+
+    /// Returns `true` if the cell lies inside the maze
+    #[must_use]
+    pub fn contains(&self, cell: Cell) -> bool {
+        self.walls.contains(cell)
+    }
+
+    /// All cells of the maze, row by row starting at `(0, 0)`
+    pub fn cells(&self) -> impl Iterator<Item = Cell> {
+        self.walls.cells()
+    }
+
+    /// The adjacent cell in the given direction, or `None` if it lies outside the maze
+    #[must_use]
+    pub fn neighbor(&self, cell: Cell, direction: Direction) -> Option<Cell> {
+        cell.step(direction).filter(|&n| self.contains(n))
+    }
+
+    /// The start cell in the bottom left corner
+    #[must_use]
+    pub fn start(&self) -> Cell {
+        Cell::new(0, 0)
+    }
+
+    /// The goal cells in the center of the maze.
+    ///
+    /// These are the middle four cells for even dimensions and fewer for odd ones.
+    #[must_use]
+    pub fn goals(&self) -> Vec<Cell> {
+        let center = |len: u32| {
+            if len.is_multiple_of(2) {
+                (len / 2).saturating_sub(1)..=len / 2
+            } else {
+                len / 2..=len / 2
+            }
+        };
+        center(self.height())
+            .flat_map(|y| center(self.width()).map(move |x| Cell::new(x, y)))
+            .collect()
+    }
+
+    /// Returns `true` if the cell is one of the goal cells
+    #[must_use]
+    pub fn is_goal(&self, cell: Cell) -> bool {
+        self.goals().contains(&cell)
+    }
+
+    /// What is known about the wall on the given side of the cell.
+    ///
+    /// Cells outside the maze are surrounded by walls.
+    #[must_use]
+    pub fn wall(&self, cell: Cell, direction: Direction) -> WallState {
+        self.walls
+            .get(cell)
+            .map_or(WallState::Wall, |walls| walls[direction.index()])
+    }
+
+    /// Returns the state of all four walls of the cell, indexed by `Direction`
+    #[must_use]
+    pub fn walls(&self, cell: Cell) -> [WallState; 4] {
+        Direction::ALL.map(|direction| self.wall(cell, direction))
+    }
+
+    /// Records the state of the wall on the given side of the cell and on the matching side of
+    /// its neighbour. Boundary walls and cells outside the maze are left untouched.
+    pub fn set_wall(&mut self, cell: Cell, direction: Direction, state: WallState) {
+        let Some(neighbor) = self
+            .neighbor(cell, direction)
+            .filter(|_| self.contains(cell))
+        else {
+            return;
+        };
+        self.walls[cell][direction.index()] = state;
+        self.walls[neighbor][direction.opposite().index()] = state;
+    }
+
+    /// Records whether a wall was sensed on the given side of the cell
+    pub fn record_wall(&mut self, cell: Cell, direction: Direction, present: bool) {
+        self.set_wall(cell, direction, present.into());
+    }
+
+    /// Records the three wall sensor readings taken at the given pose and marks the cell as
+    /// visited
+    pub fn record_sensors(&mut self, pose: &Pose, front: bool, left: bool, right: bool) {
+        self.record_wall(pose.cell, pose.heading, front);
+        self.record_wall(pose.cell, pose.heading.left(), left);
+        self.record_wall(pose.cell, pose.heading.right(), right);
+        self.mark_visited(pose.cell);
+    }
+
+    /// Returns `true` if the mouse has been in the cell
+    #[must_use]
+    pub fn is_visited(&self, cell: Cell) -> bool {
+        self.visited.get(cell).copied().unwrap_or(false)
+    }
+
+    pub fn mark_visited(&mut self, cell: Cell) {
+        if let Some(visited) = self.visited.get_mut(cell) {
+            *visited = true;
+        }
+    }
+
+    /// Returns `true` if all four walls of the cell are known
+    #[must_use]
+    pub fn is_explored(&self, cell: Cell) -> bool {
+        self.walls(cell).iter().all(|wall| wall.is_known())
+    }
+
+    /// The number of walls, including open ones, whose state is known
+    #[must_use]
+    pub fn known_walls(&self) -> usize {
+        self.cells()
+            .flat_map(|cell| self.walls(cell))
+            .filter(|wall| wall.is_known())
+            .count()
+    }
+
+    /// The number of walls inside the maze whose state is known, counting every wall once and
+    /// leaving out the boundary
+    #[must_use]
+    pub fn sensed_walls(&self) -> usize {
+        self.cells()
+            .flat_map(|cell| [Direction::North, Direction::East].map(|d| (cell, d)))
+            .filter(|&(cell, direction)| {
+                self.neighbor(cell, direction).is_some() && self.wall(cell, direction).is_known()
+            })
+            .count()
+    }
+
+    /// Returns `true` if both mazes have the same size and no wall known in both differs
+    #[must_use]
+    pub fn agrees_with(&self, other: &Maze) -> bool {
+        self.width() == other.width()
+            && self.height() == other.height()
+            && self.cells().all(|cell| {
+                self.walls(cell)
+                    .iter()
+                    .zip(other.walls(cell))
+                    .all(|(&a, b)| !a.is_known() || !b.is_known() || a == b)
+            })
+    }
+
+    /// Copies every known wall and visited cell of `other` into this maze. Walls known in
+    /// `other` take precedence.
+    pub fn merge(&mut self, other: &Maze) {
+        for cell in other.cells() {
+            for direction in Direction::ALL {
+                let wall = other.wall(cell, direction);
+                if wall.is_known() {
+                    self.set_wall(cell, direction, wall);
+                }
+            }
+            if other.is_visited(cell) {
+                self.mark_visited(cell);
+            }
+        }
+    }
+
+    /// A hash of the maze size and every known wall, used to tell mazes apart. Visited cells do
+    /// not change the fingerprint.
+    #[must_use]
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+        let walls = self.cells().flat_map(|cell| {
+            [Direction::North, Direction::East].map(|direction| self.wall(cell, direction) as u8)
+        });
+        self.width()
+            .to_le_bytes()
+            .into_iter()
+            .chain(self.height().to_le_bytes())
+            .chain(walls)
+            .fold(FNV_OFFSET, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+}
+
+/// Where the mouse is and which way it is facing
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Pose {
+    pub cell: Cell,
+    pub heading: Direction,
+}
+
+impl Pose {
+    #[must_use]
+    pub const fn new(cell: Cell, heading: Direction) -> Self {
+        Self { cell, heading }
+    }
+
+    /// The pose mms puts the mouse in at the start and after a reset: `(0, 0)` facing north
+    #[must_use]
+    pub const fn start() -> Self {
+        Self::new(Cell::new(0, 0), Direction::North)
+    }
+
+    /// Tracks a `turn_left` command
+    pub fn turn_left(&mut self) {
+        self.heading = self.heading.left();
+    }
+
+    /// Tracks a `turn_right` command
+    pub fn turn_right(&mut self) {
+        self.heading = self.heading.right();
+    }
+
+    /// Tracks a `move_forward` command over the given number of cells
+    pub fn move_forward(&mut self, distance: u32) {
+        for _ in 0..distance {
+            if let Some(cell) = self.cell.step(self.heading) {
+                self.cell = cell;
+            }
+        }
+    }
+}
+
+impl Default for Pose {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundary_is_known() {
+        let maze = Maze::new(3, 2);
+        assert_eq!(
+            maze.wall(Cell::new(0, 0), Direction::South),
+            WallState::Wall
+        );
+        assert_eq!(maze.wall(Cell::new(0, 0), Direction::West), WallState::Wall);
+        assert_eq!(
+            maze.wall(Cell::new(0, 0), Direction::North),
+            WallState::Unknown
+        );
+        assert_eq!(maze.wall(Cell::new(2, 1), Direction::East), WallState::Wall);
+        assert_eq!(
+            maze.wall(Cell::new(5, 5), Direction::North),
+            WallState::Wall
+        );
+        assert_eq!(maze.known_walls(), 2 * (3 + 2));
+        assert_eq!(maze.sensed_walls(), 0);
+    }
+
+    #[test]
+    fn walls_are_shared_with_the_neighbor() {
+        let mut maze = Maze::new(3, 3);
+        maze.set_wall(Cell::new(1, 1), Direction::East, WallState::Wall);
+        assert_eq!(maze.wall(Cell::new(2, 1), Direction::West), WallState::Wall);
+        assert_eq!(maze.sensed_walls(), 1);
+
+        // boundary walls stay walls
+        maze.set_wall(Cell::new(0, 0), Direction::West, WallState::Open);
+        assert_eq!(maze.wall(Cell::new(0, 0), Direction::West), WallState::Wall);
+    }
+
+    #[test]
+    fn sensors_are_relative_to_the_heading() {
+        let mut maze = Maze::new(3, 3);
+        let pose = Pose::new(Cell::new(1, 1), Direction::East);
+        maze.record_sensors(&pose, true, false, true);
+        assert_eq!(
+            maze.walls(Cell::new(1, 1)),
+            [
+                WallState::Open,
+                WallState::Wall,
+                WallState::Wall,
+                WallState::Unknown
+            ]
+        );
+        assert!(maze.is_visited(Cell::new(1, 1)));
+        assert!(!maze.is_explored(Cell::new(1, 1)));
+    }
+
+    #[test]
+    fn goals_are_in_the_center() {
+        assert_eq!(Maze::new(4, 4).goals().len(), 4);
+        assert_eq!(Maze::new(3, 3).goals(), vec![Cell::new(1, 1)]);
+        assert_eq!(
+            Maze::new(5, 4).goals(),
+            vec![Cell::new(2, 1), Cell::new(2, 2)]
+        );
+    }
+
+    #[test]
+    fn merge_and_agreement() {
+        let mut a = Maze::new(3, 3);
+        let mut b = Maze::new(3, 3);
+        a.set_wall(Cell::new(0, 0), Direction::North, WallState::Wall);
+        b.set_wall(Cell::new(1, 1), Direction::North, WallState::Open);
+        assert!(a.agrees_with(&b));
+        assert_ne!(a.fingerprint(), b.fingerprint());
+
+        a.merge(&b);
+        assert_eq!(a.wall(Cell::new(1, 1), Direction::North), WallState::Open);
+
+        b.set_wall(Cell::new(0, 0), Direction::North, WallState::Open);
+        assert!(!a.agrees_with(&b));
+        assert!(!a.agrees_with(&Maze::new(3, 4)));
+    }
+
+    #[test]
+    fn pose_tracks_moves() {
+        let mut pose = Pose::start();
+        pose.move_forward(2);
+        pose.turn_right();
+        pose.move_forward(1);
+        assert_eq!(pose, Pose::new(Cell::new(1, 2), Direction::East));
+        pose.turn_left();
+        pose.turn_left();
+        assert_eq!(pose.heading, Direction::West);
+    }
+}
@@ -0,0 +1,365 @@
+//! Drawing planned and executed paths on top of the maze.
+//!
+//! Overlays are stacked in the order they were added. Every cell shows the color and text of
+//! the top-most overlay covering it, and whatever was drawn before the first overlay once no
+//! overlay covers it anymore. That way a single path can be removed without wiping other colors.
+
+use std::collections::HashMap;
+
+use crate::{
+    canvas::{Canvas, DrawTarget},
+    layers::Layer,
+    maze::Cell,
+    solver::path_directions,
+    CellColor, Direction, ResultType,
+};
+
+/// Which texts a path overlay writes into its cells
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Annotation {
+    /// Only color the cells
+    #[default]
+    None,
+    /// Write the step number into every cell, starting at `0`
+    StepNumbers,
+    /// Mark the cells where the path turns with `L`, `R` or `U` for turning around
+    TurnPoints,
+}
+
+/// A path drawn in a single color
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathOverlay {
+    cells: Vec<Cell>,
+    color: CellColor,
+    annotation: Annotation,
+}
+
+impl PathOverlay {
+    /// Creates an overlay for a path of adjacent cells, as returned by
+    /// [`shortest_path`](crate::solver::shortest_path)
+    #[must_use]
+    pub fn new(cells: Vec<Cell>, color: CellColor) -> Self {
+        Self {
+            cells,
+            color,
+            annotation: Annotation::None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_annotation(mut self, annotation: Annotation) -> Self {
+        self.annotation = annotation;
+        self
+    }
+
+    #[must_use]
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    #[must_use]
+    pub fn color(&self) -> CellColor {
+        self.color
+    }
+
+    /// Returns `true` if the path goes through the cell
+    #[must_use]
+    pub fn covers(&self, cell: Cell) -> bool {
+        self.cells.contains(&cell)
+    }
+
+    /// The text the overlay writes into the cell, if any. For cells that are visited more than
+    /// once the last visit wins.
+    #[must_use]
+    pub fn text(&self, cell: Cell) -> Option<String> {
+        match self.annotation {
+            Annotation::None => None,
+            Annotation::StepNumbers => self
+                .cells
+                .iter()
+                .rposition(|&c| c == cell)
+                .map(|step| step.to_string()),
+            Annotation::TurnPoints => {
+                let directions = path_directions(&self.cells);
+                directions
+                    .windows(2)
+                    .zip(&self.cells[1..])
+                    .rev()
+                    .filter(|&(_, &c)| c == cell)
+                    .find_map(|(pair, _)| turn_mark(pair[0], pair[1]))
+                    .map(str::to_string)
+            }
+        }
+    }
+
+    /// Draws the path into a layer of a [`LayerStack`](crate::layers::LayerStack)
+    pub fn draw_into(&self, layer: &mut Layer) {
+        for &cell in &self.cells {
+            layer.set_color(cell, self.color);
+            if let Some(text) = self.text(cell) {
+                layer.set_text(cell, &text);
+            }
+        }
+    }
+}
+
+fn turn_mark(from: Direction, to: Direction) -> Option<&'static str> {
+    if to == from.left() {
+        Some("L")
+    } else if to == from.right() {
+        Some("R")
+    } else if to == from.opposite() {
+        Some("U")
+    } else {
+        None
+    }
+}
+
+/// Identifies an overlay added to [`PathOverlays`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OverlayId(u64);
+
+/// A stack of path overlays drawn through a [`Canvas`].
+///
+/// The colors and texts below the overlays are remembered when a cell gets covered for the first
+/// time and restored once no overlay covers it anymore. Anything drawn into a covered cell in the
+/// meantime is lost when the cell is restored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PathOverlays {
+    below: HashMap<Cell, (Option<CellColor>, String)>,
+    overlays: Vec<(OverlayId, PathOverlay)>,
+    next_id: u64,
+}
+
+impl PathOverlays {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the overlay with the given id
+    #[must_use]
+    pub fn get(&self, id: OverlayId) -> Option<&PathOverlay> {
+        self.overlays
+            .iter()
+            .find(|(overlay_id, _)| *overlay_id == id)
+            .map(|(_, overlay)| overlay)
+    }
+
+    /// Draws the overlay on top of all others
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn add<D: DrawTarget>(
+        &mut self,
+        canvas: &mut Canvas<D>,
+        overlay: PathOverlay,
+    ) -> ResultType<OverlayId> {
+        for &cell in overlay.cells() {
+            if let Some(shown) = canvas.shown().get(cell) {
+                self.below
+                    .entry(cell)
+                    .or_insert_with(|| (shown.color, shown.text.clone()));
+            }
+        }
+        let id = OverlayId(self.next_id);
+        self.next_id += 1;
+        let cells = overlay.cells().to_vec();
+        self.overlays.push((id, overlay));
+        try_api!(self.redraw(canvas, &cells));
+        return_result!(id);
+    }
+
+    /// Removes the overlay and restores what is below it
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn remove<D: DrawTarget>(
+        &mut self,
+        canvas: &mut Canvas<D>,
+        id: OverlayId,
+    ) -> ResultType<Option<PathOverlay>> {
+        let index = self
+            .overlays
+            .iter()
+            .position(|(overlay_id, _)| *overlay_id == id);
+        let removed = index.map(|index| self.overlays.remove(index).1);
+        if let Some(overlay) = &removed {
+            try_api!(self.redraw(canvas, overlay.cells()));
+        }
+        return_result!(removed);
+    }
+
+    /// Removes all overlays and restores what is below them
+    ///
+    /// # Errors
+    /// `IoError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn clear<D: DrawTarget>(&mut self, canvas: &mut Canvas<D>) -> ResultType<()> {
+        let cells: Vec<Cell> = self.below.keys().copied().collect();
+        self.overlays.clear();
+        try_api!(self.redraw(canvas, &cells));
+        return_result!(());
+    }
+
+    fn redraw<D: DrawTarget>(&mut self, canvas: &mut Canvas<D>, cells: &[Cell]) -> ResultType<()> {
+        for &cell in cells {
+            let Some((below_color, below_text)) = self.below.get(&cell).cloned() else {
+                continue;
+            };
+            let mut covering = self
+                .overlays
+                .iter()
+                .rev()
+                .map(|(_, overlay)| overlay)
+                .filter(|overlay| overlay.covers(cell))
+                .peekable();
+            let color = covering.peek().map_or(below_color, |top| Some(top.color));
+            let text = covering
+                .find_map(|overlay| overlay.text(cell))
+                .unwrap_or(below_text);
+            match color {
+                Some(color) => try_api!(canvas.set_color(cell, color)),
+                None => try_api!(canvas.clear_color(cell)),
+            }
+            try_api!(canvas.set_text(cell, &text));
+            if !self
+                .overlays
+                .iter()
+                .any(|(_, overlay)| overlay.covers(cell))
+            {
+                self.below.remove(&cell);
+            }
+        }
+        return_result!(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canvas::testing::{canvas, sent, TestCanvas};
+
+    fn cells(xs: &[u32]) -> Vec<Cell> {
+        xs.iter().map(|&x| Cell::new(x, 0)).collect()
+    }
+
+    fn shown(canvas: &TestCanvas, x: u32) -> (Option<CellColor>, &str) {
+        let shown = &canvas.shown()[Cell::new(x, 0)];
+        (shown.color, &shown.text)
+    }
+
+    /// A 4x1 canvas with a green `g` in `(1, 0)`, covered by a red path over the first three
+    /// cells and a numbered blue path over the last three
+    fn stacked() -> (TestCanvas, PathOverlays, OverlayId, OverlayId) {
+        let mut canvas = canvas(4, 1);
+        unwrap_api!(canvas.set_color(Cell::new(1, 0), CellColor::Green));
+        unwrap_api!(canvas.set_text(Cell::new(1, 0), "g"));
+        let mut overlays = PathOverlays::new();
+        let red = unwrap_api!(overlays.add(
+            &mut canvas,
+            PathOverlay::new(cells(&[0, 1, 2]), CellColor::Red)
+        ));
+        let blue = unwrap_api!(overlays.add(
+            &mut canvas,
+            PathOverlay::new(cells(&[1, 2, 3]), CellColor::Blue)
+                .with_annotation(Annotation::StepNumbers)
+        ));
+        sent(&mut canvas);
+        (canvas, overlays, red, blue)
+    }
+
+    #[test]
+    fn the_top_most_overlay_is_shown() {
+        let (canvas, overlays, red, blue) = stacked();
+        assert_eq!(shown(&canvas, 0), (Some(CellColor::Red), ""));
+        assert_eq!(shown(&canvas, 1), (Some(CellColor::Blue), "0"));
+        assert_eq!(shown(&canvas, 3), (Some(CellColor::Blue), "2"));
+        assert_eq!(
+            overlays.get(red).map(PathOverlay::color),
+            Some(CellColor::Red)
+        );
+        assert!(overlays.get(blue).is_some());
+    }
+
+    #[test]
+    fn removing_the_top_overlay_uncovers_the_one_below() {
+        let (mut canvas, mut overlays, red, blue) = stacked();
+        let removed = unwrap_api!(overlays.remove(&mut canvas, blue));
+        assert_eq!(
+            removed.map(|overlay| overlay.color()),
+            Some(CellColor::Blue)
+        );
+        assert_eq!(
+            sent(&mut canvas),
+            [
+                "setColor 1 0 r",
+                "setText 1 0 g",
+                "setColor 2 0 r",
+                "clearText 2 0",
+                "clearColor 3 0",
+                "clearText 3 0",
+            ]
+        );
+
+        unwrap_api!(overlays.remove(&mut canvas, red));
+        assert_eq!(shown(&canvas, 0), (None, ""));
+        assert_eq!(shown(&canvas, 1), (Some(CellColor::Green), "g"));
+        assert_eq!(shown(&canvas, 2), (None, ""));
+        assert_eq!(
+            overlays,
+            PathOverlays {
+                next_id: 2,
+                ..PathOverlays::new()
+            }
+        );
+    }
+
+    #[test]
+    fn removing_a_covered_overlay_keeps_the_top_one() {
+        let (mut canvas, mut overlays, red, blue) = stacked();
+        unwrap_api!(overlays.remove(&mut canvas, red));
+        assert_eq!(sent(&mut canvas), ["clearColor 0 0"]);
+        assert_eq!(shown(&canvas, 1), (Some(CellColor::Blue), "0"));
+
+        unwrap_api!(overlays.remove(&mut canvas, blue));
+        assert_eq!(shown(&canvas, 1), (Some(CellColor::Green), "g"));
+        assert_eq!(shown(&canvas, 3), (None, ""));
+    }
+
+    #[test]
+    fn clear_restores_every_cell() {
+        let (mut canvas, mut overlays, red, _) = stacked();
+        unwrap_api!(overlays.clear(&mut canvas));
+        assert_eq!(shown(&canvas, 1), (Some(CellColor::Green), "g"));
+        for x in [0, 2, 3] {
+            assert_eq!(shown(&canvas, x), (None, ""));
+        }
+        assert_eq!(unwrap_api!(overlays.remove(&mut canvas, red)), None);
+        assert!(!sent(&mut canvas).is_empty());
+        unwrap_api!(overlays.clear(&mut canvas));
+        assert!(sent(&mut canvas).is_empty());
+    }
+
+    #[test]
+    fn turn_points_are_marked() {
+        let path = vec![
+            Cell::new(0, 0),
+            Cell::new(0, 1),
+            Cell::new(1, 1),
+            Cell::new(1, 0),
+            Cell::new(0, 0),
+        ];
+        let overlay =
+            PathOverlay::new(path, CellColor::Red).with_annotation(Annotation::TurnPoints);
+        assert_eq!(overlay.text(Cell::new(0, 1)).as_deref(), Some("R"));
+        assert_eq!(overlay.text(Cell::new(1, 1)).as_deref(), Some("R"));
+        assert_eq!(overlay.text(Cell::new(0, 0)), None);
+        assert_eq!(overlay.text(Cell::new(5, 5)), None);
+    }
+}
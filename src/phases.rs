@@ -0,0 +1,289 @@
+//! The classic explore, return and speed run structure as a [`MouseController`].
+//!
+//! mms keeps separate stats for the current and the best run, so a bot first explores, then
+//! drives the best known path as fast as possible. Between the phases the stats are read to
+//! decide whether another speed run could still beat the best run so far.
+
+use std::collections::VecDeque;
+
+use crate::{
+    bot::{path_actions, Action, BotState, MouseController},
+    explore::{is_optimal, ExplorePhase, Explorer},
+    score::path_totals,
+    solver::{shortest_path, Assumption},
+    stats::{RunStats, Totals},
+    ResultType,
+};
+
+/// The phase the bot is in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// Exploring the maze with the [`Explorer`], which ends on the start cell. Skipped when the
+    /// maze is already known well enough, e.g. because it was restored from a
+    /// [`MazeStore`](crate::store::MazeStore).
+    #[default]
+    Explore,
+    /// Driving the best known path from the start to the goal
+    SpeedRun,
+    /// Driving back from the goal to the start over known cells
+    Return,
+    /// Nothing left to gain
+    Done,
+}
+
+/// A controller that explores the maze and then performs speed runs until they stop paying off.
+///
+/// Before every speed run the best run is read from mms. The run is skipped if the best known
+/// path is estimated to cost more than the best run. A path that only ties it is driven again,
+/// up to `max_speed_runs` times, since the estimate of [`path_totals`] can differ from what mms
+/// measures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhasedBot {
+    phase: Phase,
+    explorer: Explorer,
+    planned: VecDeque<Action>,
+    speed_runs: u32,
+    max_speed_runs: u32,
+}
+
+impl Default for PhasedBot {
+    fn default() -> Self {
+        Self::new(3)
+    }
+}
+
+impl PhasedBot {
+    /// Creates a bot that performs at most `max_speed_runs` speed runs
+    #[must_use]
+    pub fn new(max_speed_runs: u32) -> Self {
+        Self {
+            phase: Phase::Explore,
+            explorer: Explorer::new(),
+            planned: VecDeque::new(),
+            speed_runs: 0,
+            max_speed_runs,
+        }
+    }
+
+    #[must_use]
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// The number of speed runs that were started
+    #[must_use]
+    pub fn speed_runs(&self) -> u32 {
+        self.speed_runs
+    }
+
+    /// Plans the next speed run if it could beat or tie the best run so far, which is only read
+    /// once a path to the goal is known
+    fn plan_speed_run(
+        &mut self,
+        state: &BotState,
+        best_run: &mut impl FnMut() -> ResultType<Option<Totals>>,
+    ) -> ResultType<Phase> {
+        if self.speed_runs >= self.max_speed_runs {
+            return_result!(Phase::Done);
+        }
+        let Some(path) = shortest_path(
+            &state.maze,
+            &state.pose,
+            &state.maze.goals(),
+            Assumption::Pessimistic,
+        ) else {
+            return_result!(Phase::Done);
+        };
+        let candidate = path_totals(&state.pose, &path);
+        if try_api!(best_run()).is_some_and(|best| candidate.cost() > best.cost()) {
+            return_result!(Phase::Done);
+        }
+
+        self.planned = path_actions(&state.pose, &path).into();
+        self.speed_runs += 1;
+        return_result!(Phase::SpeedRun);
+    }
+
+    fn plan_return(&mut self, state: &BotState) -> Phase {
+        let start = state.maze.start();
+        match shortest_path(&state.maze, &state.pose, &[start], Assumption::Pessimistic) {
+            Some(path) => {
+                self.planned = path_actions(&state.pose, &path).into();
+                Phase::Return
+            }
+            None => Phase::Done,
+        }
+    }
+
+    /// Like [`MouseController::step`], but reads the best run from `best` instead of mms
+    fn next_action(
+        &mut self,
+        state: &BotState,
+        mut best: impl FnMut() -> ResultType<Option<Totals>>,
+    ) -> ResultType<Action> {
+        loop {
+            if let Some(action) = self.planned.pop_front() {
+                return_result!(action);
+            }
+            let known_maze = self.explorer.phase() == ExplorePhase::ToGoal
+                && state.pose.cell == state.maze.start()
+                && is_optimal(&state.maze);
+            self.phase = match self.phase {
+                Phase::Explore if known_maze => try_api!(self.plan_speed_run(state, &mut best)),
+                Phase::Explore => match self.explorer.next_move(&state.maze, &state.pose) {
+                    Some(direction) => {
+                        return_result!(Action::towards(&state.pose, direction));
+                    }
+                    None => try_api!(self.plan_speed_run(state, &mut best)),
+                },
+                Phase::SpeedRun => self.plan_return(state),
+                Phase::Return => try_api!(self.plan_speed_run(state, &mut best)),
+                Phase::Done => {
+                    return_result!(Action::Stop);
+                }
+            };
+        }
+    }
+}
+
+impl MouseController for PhasedBot {
+    fn step(&mut self, state: &BotState) -> ResultType<Action> {
+        self.next_action(state, || {
+            let stats = try_api!(RunStats::fetch());
+            return_result!(stats.best_run());
+        })
+    }
+
+    fn reset(&mut self) {
+        *self = Self::new(self.max_speed_runs);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        maze::{Maze, Pose, WallState},
+        Direction,
+    };
+
+    /// A 3x3 maze where every wall inside is known to be open
+    fn known_maze() -> Maze {
+        let mut maze = Maze::new(3, 3);
+        for cell in maze.clone().cells() {
+            for direction in Direction::ALL {
+                maze.set_wall(cell, direction, WallState::Open);
+            }
+        }
+        maze
+    }
+
+    /// The totals of the speed run the bot plans from the pose
+    fn speed_run_totals(maze: &Maze, pose: &Pose) -> Totals {
+        let path = shortest_path(maze, pose, &maze.goals(), Assumption::Pessimistic).unwrap();
+        path_totals(pose, &path)
+    }
+
+    /// Drives the bot until it stops, moving the pose like mms would. Returns the phases in the
+    /// order they were entered and how often the best run was read.
+    fn drive(
+        bot: &mut PhasedBot,
+        state: &mut BotState,
+        mut best_run: impl FnMut(&BotState) -> Option<Totals>,
+    ) -> (Vec<Phase>, usize) {
+        let mut phases = vec![bot.phase()];
+        let mut reads = 0;
+        for _ in 0..100 {
+            let current: &BotState = state;
+            let action = unwrap_api!(bot.next_action(current, || {
+                reads += 1;
+                return_result!(best_run(current));
+            }));
+            if phases.last() != Some(&bot.phase()) {
+                phases.push(bot.phase());
+            }
+            match action {
+                Action::MoveForward(distance) => state.pose.move_forward(distance.get()),
+                Action::TurnLeft => state.pose.turn_left(),
+                Action::TurnRight => state.pose.turn_right(),
+                Action::Stop => return (phases, reads),
+            }
+        }
+        panic!("the bot did not stop");
+    }
+
+    #[test]
+    fn a_known_maze_skips_exploration_and_ties_are_driven_again() {
+        let maze = known_maze();
+        let mut state = BotState::new(maze.clone());
+        let mut bot = PhasedBot::new(2);
+        // mms recorded exactly the run the bot plans, wherever the return left the mouse facing
+        let (phases, reads) = drive(&mut bot, &mut state, |state| {
+            Some(speed_run_totals(&state.maze, &state.pose))
+        });
+        use Phase::{Done, Explore, Return, SpeedRun};
+        assert_eq!(phases, [Explore, SpeedRun, Return, SpeedRun, Return, Done]);
+        assert_eq!(reads, 2);
+        assert_eq!(bot.speed_runs(), 2);
+        assert_eq!(state.pose.cell, maze.start());
+    }
+
+    #[test]
+    fn speed_runs_stop_when_the_best_run_is_better() {
+        let maze = known_maze();
+        let mut best = speed_run_totals(&maze, &Pose::start());
+        best.turns -= 1;
+        let mut state = BotState::new(maze);
+        let mut bot = PhasedBot::new(3);
+        let (phases, reads) = drive(&mut bot, &mut state, |_| Some(best));
+        assert_eq!(phases, [Phase::Explore, Phase::Done]);
+        assert_eq!(reads, 1);
+        assert_eq!(bot.speed_runs(), 0);
+    }
+
+    #[test]
+    fn without_a_best_run_every_allowed_speed_run_is_driven() {
+        let mut state = BotState::new(known_maze());
+        let mut bot = PhasedBot::new(3);
+        drive(&mut bot, &mut state, |_| None);
+        assert_eq!(bot.speed_runs(), 3);
+
+        let mut bot = PhasedBot::new(0);
+        let (phases, reads) = drive(&mut bot, &mut state, |_| None);
+        assert_eq!(phases, [Phase::Explore, Phase::Done]);
+        assert_eq!(reads, 0);
+    }
+
+    #[test]
+    fn an_unknown_maze_is_explored_first() {
+        let real = known_maze();
+        let mut state = BotState::new(Maze::new(3, 3));
+        let mut bot = PhasedBot::new(1);
+        for _ in 0..100 {
+            let wall = |pose: &Pose, direction| real.wall(pose.cell, direction) == WallState::Wall;
+            let pose = state.pose;
+            state.maze.record_sensors(
+                &pose,
+                wall(&pose, pose.heading),
+                wall(&pose, pose.heading.left()),
+                wall(&pose, pose.heading.right()),
+            );
+            match unwrap_api!(bot.next_action(&state, || {
+                return_result!(None);
+            })) {
+                Action::MoveForward(distance) => state.pose.move_forward(distance.get()),
+                Action::TurnLeft => state.pose.turn_left(),
+                Action::TurnRight => state.pose.turn_right(),
+                Action::Stop => break,
+            }
+            if bot.phase() == Phase::SpeedRun {
+                assert!(state.maze.sensed_walls() > 0);
+            }
+        }
+        assert_eq!(bot.phase(), Phase::Done);
+        assert_eq!(bot.speed_runs(), 1);
+
+        bot.reset();
+        assert_eq!(bot, PhasedBot::new(1));
+    }
+}
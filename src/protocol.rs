@@ -0,0 +1,188 @@
+//! The mms commands and their responses as typed values.
+//!
+//! A [`Command`] is run with [`Connection::execute`](crate::connection::Connection::execute),
+//! which answers with a [`Response`]. With the `serde` feature a command is (de)serialized with
+//! its name in `cmd`, e.g. `{"cmd":"setColor","x":0,"y":0,"color":"DarkGreen"}`.
+
+use std::num::NonZeroU32;
+
+use crate::{CellColor, Direction, Stat, StatQuery};
+
+/// A single mms command, named like in the mms protocol
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "cmd", rename_all = "camelCase")
+)]
+pub enum Command {
+    MazeWidth,
+    MazeHeight,
+    WallFront,
+    WallRight,
+    WallLeft,
+    /// Moves forward by `distance` cells, or by one without a distance
+    MoveForward {
+        #[cfg_attr(feature = "serde", serde(default))]
+        distance: Option<NonZeroU32>,
+    },
+    TurnRight,
+    TurnLeft,
+    SetWall {
+        x: u32,
+        y: u32,
+        direction: Direction,
+    },
+    ClearWall {
+        x: u32,
+        y: u32,
+        direction: Direction,
+    },
+    SetColor {
+        x: u32,
+        y: u32,
+        color: CellColor,
+    },
+    ClearColor {
+        x: u32,
+        y: u32,
+    },
+    ClearAllColor,
+    SetText {
+        x: u32,
+        y: u32,
+        text: String,
+    },
+    ClearText {
+        x: u32,
+        y: u32,
+    },
+    ClearAllText,
+    WasReset,
+    AckReset,
+    GetStat {
+        query: StatQuery,
+    },
+}
+
+/// The answer to a [`Command`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Response {
+    /// mms does not answer the command, e.g. `setColor`
+    None,
+    /// mms acknowledged a movement or reset
+    Ack,
+    /// The maze width or height
+    Size(i32),
+    /// Whether there is a wall, or whether the mouse was reset
+    Bool(bool),
+    Stat(Stat),
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    /// Every command together with its JSON form
+    fn commands() -> Vec<(Command, &'static str)> {
+        vec![
+            (Command::MazeWidth, r#"{"cmd":"mazeWidth"}"#),
+            (Command::MazeHeight, r#"{"cmd":"mazeHeight"}"#),
+            (Command::WallFront, r#"{"cmd":"wallFront"}"#),
+            (Command::WallRight, r#"{"cmd":"wallRight"}"#),
+            (Command::WallLeft, r#"{"cmd":"wallLeft"}"#),
+            (
+                Command::MoveForward { distance: None },
+                r#"{"cmd":"moveForward","distance":null}"#,
+            ),
+            (
+                Command::MoveForward {
+                    distance: NonZeroU32::new(3),
+                },
+                r#"{"cmd":"moveForward","distance":3}"#,
+            ),
+            (Command::TurnRight, r#"{"cmd":"turnRight"}"#),
+            (Command::TurnLeft, r#"{"cmd":"turnLeft"}"#),
+            (
+                Command::SetWall {
+                    x: 1,
+                    y: 2,
+                    direction: Direction::North,
+                },
+                r#"{"cmd":"setWall","x":1,"y":2,"direction":"North"}"#,
+            ),
+            (
+                Command::ClearWall {
+                    x: 1,
+                    y: 2,
+                    direction: Direction::West,
+                },
+                r#"{"cmd":"clearWall","x":1,"y":2,"direction":"West"}"#,
+            ),
+            (
+                Command::SetColor {
+                    x: 0,
+                    y: 0,
+                    color: CellColor::DarkGreen,
+                },
+                r#"{"cmd":"setColor","x":0,"y":0,"color":"DarkGreen"}"#,
+            ),
+            (
+                Command::ClearColor { x: 3, y: 4 },
+                r#"{"cmd":"clearColor","x":3,"y":4}"#,
+            ),
+            (Command::ClearAllColor, r#"{"cmd":"clearAllColor"}"#),
+            (
+                Command::SetText {
+                    x: 5,
+                    y: 6,
+                    text: "a b".to_string(),
+                },
+                r#"{"cmd":"setText","x":5,"y":6,"text":"a b"}"#,
+            ),
+            (
+                Command::ClearText { x: 7, y: 8 },
+                r#"{"cmd":"clearText","x":7,"y":8}"#,
+            ),
+            (Command::ClearAllText, r#"{"cmd":"clearAllText"}"#),
+            (Command::WasReset, r#"{"cmd":"wasReset"}"#),
+            (Command::AckReset, r#"{"cmd":"ackReset"}"#),
+            (
+                Command::GetStat {
+                    query: StatQuery::BestRunEffectiveDistance,
+                },
+                r#"{"cmd":"getStat","query":"BestRunEffectiveDistance"}"#,
+            ),
+        ]
+    }
+
+    #[test]
+    fn commands_round_trip_with_their_name_in_cmd() {
+        for (command, json) in commands() {
+            assert_eq!(serde_json::to_string(&command).unwrap(), json);
+            assert_eq!(serde_json::from_str::<Command>(json).unwrap(), command);
+        }
+    }
+
+    #[test]
+    fn the_distance_is_optional_and_never_zero() {
+        assert_eq!(
+            serde_json::from_str::<Command>(r#"{"cmd":"moveForward"}"#).unwrap(),
+            Command::MoveForward { distance: None }
+        );
+        assert!(serde_json::from_str::<Command>(r#"{"cmd":"moveForward","distance":0}"#).is_err());
+    }
+
+    #[test]
+    fn unknown_commands_and_values_are_rejected() {
+        for json in [
+            r#"{"cmd":"fly"}"#,
+            r#"{"cmd":"MazeWidth"}"#,
+            r#"{"x":0,"y":0}"#,
+            r#"{"cmd":"setColor","x":0,"y":0,"color":"Purple"}"#,
+            r#"{"cmd":"setWall","x":-1,"y":0,"direction":"North"}"#,
+        ] {
+            assert!(serde_json::from_str::<Command>(json).is_err(), "{json}");
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! The `mms_rs` Python extension module.
+//!
+//! Exposes [`MmsApi`](crate::MmsApi), the enums and the maze utilities, so Python bots share the
+//! protocol implementation. Cells are `(x, y)` tuples and every [`MmsError`] is raised as the
+//! Python exception of the same name, which derives from `mms_rs.MmsError`.
+
+use std::{collections::HashMap, num::NonZeroU32};
+
+use pyo3::{create_exception, exceptions::PyValueError, prelude::*};
+
+use crate::{
+    maze::{Cell, Maze, Pose, WallState},
+    solver::{self, Assumption},
+    CellColor, Direction, MmsApi, MmsError, ResultType, Stat, StatQuery,
+};
+
+mod exceptions {
+    use pyo3::{create_exception, exceptions::PyException};
+
+    create_exception!(mms_rs, MmsError, PyException, "A failed mms call");
+}
+
+create_exception!(mms_rs, ParseIntError, exceptions::MmsError);
+create_exception!(mms_rs, ParseFloatError, exceptions::MmsError);
+create_exception!(mms_rs, ParseStatQueryError, exceptions::MmsError);
+create_exception!(mms_rs, IoError, exceptions::MmsError);
+create_exception!(mms_rs, InvalidAck, exceptions::MmsError);
+create_exception!(mms_rs, InvalidColorString, exceptions::MmsError);
+create_exception!(mms_rs, InvalidDirectionString, exceptions::MmsError);
+create_exception!(mms_rs, InvalidMazeFile, exceptions::MmsError);
+create_exception!(mms_rs, InvalidMazeSize, exceptions::MmsError);
+
+impl From<MmsError> for PyErr {
+    fn from(error: MmsError) -> Self {
+        let message = error.to_string();
+        match error {
+            MmsError::ParseIntError(_) => ParseIntError::new_err(message),
+            MmsError::ParseFloatError(_) => ParseFloatError::new_err(message),
+            MmsError::ParseStatQueryError(_) => ParseStatQueryError::new_err(message),
+            MmsError::IoError(_) => IoError::new_err(message),
+            MmsError::InvalidAck(_) => InvalidAck::new_err(message),
+            MmsError::InvalidColorString(_) => InvalidColorString::new_err(message),
+            MmsError::InvalidDirectionString(_) => InvalidDirectionString::new_err(message),
+            MmsError::InvalidMazeFile(_) => InvalidMazeFile::new_err(message),
+            MmsError::InvalidMazeSize(_) => InvalidMazeSize::new_err(message),
+        }
+    }
+}
+
+/// Turns the result of an api call into a Python result. With `use_panics` a failed call has
+/// already panicked, which pyo3 raises as `PanicException`.
+#[cfg(not(feature = "use_panics"))]
+fn py<T>(result: ResultType<T>) -> PyResult<T> {
+    Ok(result?)
+}
+
+#[cfg(feature = "use_panics")]
+#[allow(clippy::unnecessary_wraps)]
+fn py<T>(result: ResultType<T>) -> PyResult<T> {
+    Ok(result)
+}
+
+/// The mms api over `stdin` and `stdout`, see [`MmsApi`]
+#[pyclass(name = "MmsApi", frozen)]
+struct PyMmsApi;
+
+#[pymethods]
+#[cfg_attr(feature = "use_panics", allow(clippy::unit_arg))]
+impl PyMmsApi {
+    #[staticmethod]
+    fn maze_width() -> PyResult<i32> {
+        py(MmsApi::maze_width())
+    }
+
+    #[staticmethod]
+    fn maze_height() -> PyResult<i32> {
+        py(MmsApi::maze_height())
+    }
+
+    #[staticmethod]
+    fn wall_front() -> PyResult<bool> {
+        py(MmsApi::wall_front())
+    }
+
+    #[staticmethod]
+    fn wall_right() -> PyResult<bool> {
+        py(MmsApi::wall_right())
+    }
+
+    #[staticmethod]
+    fn wall_left() -> PyResult<bool> {
+        py(MmsApi::wall_left())
+    }
+
+    /// Moves forward by `distance` cells, or by one without a distance. Raises `ValueError` for
+    /// a distance of `0`.
+    #[staticmethod]
+    #[pyo3(signature = (distance = None))]
+    fn move_forward(distance: Option<u32>) -> PyResult<()> {
+        let distance = distance
+            .map(|distance| {
+                NonZeroU32::new(distance)
+                    .ok_or_else(|| PyValueError::new_err("the distance has to be at least 1"))
+            })
+            .transpose()?;
+        py(MmsApi::move_forward(distance))
+    }
+
+    #[staticmethod]
+    fn turn_right() -> PyResult<()> {
+        py(MmsApi::turn_right())
+    }
+
+    #[staticmethod]
+    fn turn_left() -> PyResult<()> {
+        py(MmsApi::turn_left())
+    }
+
+    #[staticmethod]
+    fn set_wall(x: u32, y: u32, direction: Direction) -> PyResult<()> {
+        py(MmsApi::set_wall(x, y, &direction))
+    }
+
+    #[staticmethod]
+    fn clear_wall(x: u32, y: u32, direction: Direction) -> PyResult<()> {
+        py(MmsApi::clear_wall(x, y, &direction))
+    }
+
+    #[staticmethod]
+    fn set_color(x: u32, y: u32, color: CellColor) -> PyResult<()> {
+        py(MmsApi::set_color(x, y, &color))
+    }
+
+    #[staticmethod]
+    fn clear_color(x: u32, y: u32) -> PyResult<()> {
+        py(MmsApi::clear_color(x, y))
+    }
+
+    #[staticmethod]
+    fn clear_all_color() -> PyResult<()> {
+        py(MmsApi::clear_all_color())
+    }
+
+    #[staticmethod]
+    fn set_text(x: u32, y: u32, text: &str) -> PyResult<()> {
+        py(MmsApi::set_text(x, y, text))
+    }
+
+    #[staticmethod]
+    fn clear_text(x: u32, y: u32) -> PyResult<()> {
+        py(MmsApi::clear_text(x, y))
+    }
+
+    #[staticmethod]
+    fn clear_all_text() -> PyResult<()> {
+        py(MmsApi::clear_all_text())
+    }
+
+    #[staticmethod]
+    fn was_reset() -> PyResult<bool> {
+        py(MmsApi::was_reset())
+    }
+
+    #[staticmethod]
+    fn ack_reset() -> PyResult<()> {
+        py(MmsApi::ack_reset())
+    }
+
+    /// The value of the stat, an `int` for distances and turns and a `float` for effective
+    /// distances and the score. `None` while mms has no value yet.
+    #[staticmethod]
+    fn get_stat(py_: Python<'_>, query: StatQuery) -> PyResult<Py<PyAny>> {
+        let stat: Stat = py(MmsApi::get_stat(&query))?;
+        Ok(match stat.int_value() {
+            Some(value) => value.into_pyobject(py_)?.into_any().unbind(),
+            None => stat.float_value().into_pyobject(py_)?.into_any().unbind(),
+        })
+    }
+}
+
+/// The knowledge the mouse has collected about the maze, see [`Maze`]
+#[pyclass(name = "Maze")]
+struct PyMaze(Maze);
+
+#[pymethods]
+impl PyMaze {
+    #[new]
+    fn new(width: u32, height: u32) -> Self {
+        Self(Maze::new(width, height))
+    }
+
+    #[getter]
+    fn width(&self) -> u32 {
+        self.0.width()
+    }
+
+    #[getter]
+    fn height(&self) -> u32 {
+        self.0.height()
+    }
+
+    fn contains(&self, cell: (u32, u32)) -> bool {
+        self.0.contains(to_cell(cell))
+    }
+
+    fn goals(&self) -> Vec<(u32, u32)> {
+        from_cells(self.0.goals())
+    }
+
+    fn is_goal(&self, cell: (u32, u32)) -> bool {
+        self.0.is_goal(to_cell(cell))
+    }
+
+    fn wall(&self, cell: (u32, u32), direction: Direction) -> WallState {
+        self.0.wall(to_cell(cell), direction)
+    }
+
+    fn set_wall(&mut self, cell: (u32, u32), direction: Direction, state: WallState) {
+        self.0.set_wall(to_cell(cell), direction, state);
+    }
+
+    fn record_wall(&mut self, cell: (u32, u32), direction: Direction, present: bool) {
+        self.0.record_wall(to_cell(cell), direction, present);
+    }
+
+    fn record_sensors(&mut self, pose: &PyPose, front: bool, left: bool, right: bool) {
+        self.0.record_sensors(&pose.0, front, left, right);
+    }
+
+    fn is_visited(&self, cell: (u32, u32)) -> bool {
+        self.0.is_visited(to_cell(cell))
+    }
+
+    fn mark_visited(&mut self, cell: (u32, u32)) {
+        self.0.mark_visited(to_cell(cell));
+    }
+
+    fn is_explored(&self, cell: (u32, u32)) -> bool {
+        self.0.is_explored(to_cell(cell))
+    }
+
+    fn fingerprint(&self) -> u64 {
+        self.0.fingerprint()
+    }
+}
+
+/// Where the mouse is and which way it is facing, see [`Pose`]
+#[pyclass(name = "Pose")]
+struct PyPose(Pose);
+
+#[pymethods]
+impl PyPose {
+    /// The pose at the given cell and heading, or the start pose without arguments
+    #[new]
+    #[pyo3(signature = (cell = (0, 0), heading = Direction::North))]
+    fn new(cell: (u32, u32), heading: Direction) -> Self {
+        Self(Pose::new(to_cell(cell), heading))
+    }
+
+    #[getter]
+    fn cell(&self) -> (u32, u32) {
+        from_cell(self.0.cell)
+    }
+
+    #[setter]
+    fn set_cell(&mut self, cell: (u32, u32)) {
+        self.0.cell = to_cell(cell);
+    }
+
+    #[getter]
+    fn heading(&self) -> Direction {
+        self.0.heading
+    }
+
+    #[setter]
+    fn set_heading(&mut self, heading: Direction) {
+        self.0.heading = heading;
+    }
+
+    fn turn_left(&mut self) {
+        self.0.turn_left();
+    }
+
+    fn turn_right(&mut self) {
+        self.0.turn_right();
+    }
+
+    #[pyo3(signature = (distance = 1))]
+    fn move_forward(&mut self, distance: u32) {
+        self.0.move_forward(distance);
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Pose(({}, {}), {:?})",
+            self.0.cell.x, self.0.cell.y, self.0.heading
+        )
+    }
+}
+
+fn to_cell((x, y): (u32, u32)) -> Cell {
+    Cell::new(x, y)
+}
+
+fn from_cell(cell: Cell) -> (u32, u32) {
+    (cell.x, cell.y)
+}
+
+fn from_cells(cells: Vec<Cell>) -> Vec<(u32, u32)> {
+    cells.into_iter().map(from_cell).collect()
+}
+
+/// The given targets, or the goal cells of the maze without targets
+fn targets(maze: &Maze, targets: Option<Vec<(u32, u32)>>) -> Vec<Cell> {
+    targets.map_or_else(
+        || maze.goals(),
+        |targets| targets.into_iter().map(to_cell).collect(),
+    )
+}
+
+/// The distance of every reachable cell to the nearest target, see [`solver::flood`]
+#[pyfunction]
+#[pyo3(signature = (maze, targets = None, assumption = Assumption::Optimistic))]
+fn flood(
+    maze: &PyMaze,
+    targets: Option<Vec<(u32, u32)>>,
+    assumption: Assumption,
+) -> HashMap<(u32, u32), u32> {
+    let targets = self::targets(&maze.0, targets);
+    solver::flood(&maze.0, &targets, assumption)
+        .iter()
+        .filter_map(|(cell, distance)| distance.map(|distance| (from_cell(cell), distance)))
+        .collect()
+}
+
+/// The direction to go from `pose` towards the nearest target, see [`solver::next_step`]
+#[pyfunction]
+#[pyo3(signature = (maze, pose, targets = None, assumption = Assumption::Optimistic))]
+fn next_step(
+    maze: &PyMaze,
+    pose: &PyPose,
+    targets: Option<Vec<(u32, u32)>>,
+    assumption: Assumption,
+) -> Option<Direction> {
+    let targets = self::targets(&maze.0, targets);
+    let distances = solver::flood(&maze.0, &targets, assumption);
+    solver::next_step(&maze.0, &distances, &pose.0, assumption)
+}
+
+/// The shortest path from `pose` to any target, see [`solver::shortest_path`]
+#[pyfunction]
+#[pyo3(signature = (maze, pose, targets = None, assumption = Assumption::Optimistic))]
+fn shortest_path(
+    maze: &PyMaze,
+    pose: &PyPose,
+    targets: Option<Vec<(u32, u32)>>,
+    assumption: Assumption,
+) -> Option<Vec<(u32, u32)>> {
+    let targets = self::targets(&maze.0, targets);
+    solver::shortest_path(&maze.0, &pose.0, &targets, assumption).map(from_cells)
+}
+
+#[pymodule]
+fn mms_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    m.add("MmsError", py.get_type::<exceptions::MmsError>())?;
+    m.add("ParseIntError", py.get_type::<ParseIntError>())?;
+    m.add("ParseFloatError", py.get_type::<ParseFloatError>())?;
+    m.add("ParseStatQueryError", py.get_type::<ParseStatQueryError>())?;
+    m.add("IoError", py.get_type::<IoError>())?;
+    m.add("InvalidAck", py.get_type::<InvalidAck>())?;
+    m.add("InvalidColorString", py.get_type::<InvalidColorString>())?;
+    m.add(
+        "InvalidDirectionString",
+        py.get_type::<InvalidDirectionString>(),
+    )?;
+    m.add("InvalidMazeFile", py.get_type::<InvalidMazeFile>())?;
+    m.add("InvalidMazeSize", py.get_type::<InvalidMazeSize>())?;
+    m.add_class::<Direction>()?;
+    m.add_class::<CellColor>()?;
+    m.add_class::<StatQuery>()?;
+    m.add_class::<WallState>()?;
+    m.add_class::<Assumption>()?;
+    m.add_class::<PyMmsApi>()?;
+    m.add_class::<PyMaze>()?;
+    m.add_class::<PyPose>()?;
+    m.add_function(wrap_pyfunction!(flood, m)?)?;
+    m.add_function(wrap_pyfunction!(next_step, m)?)?;
+    m.add_function(wrap_pyfunction!(shortest_path, m)?)?;
+    Ok(())
+}
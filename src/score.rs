@@ -0,0 +1,210 @@
+//! Predicts how the mms stats, and therefore the `Score`, come out for a candidate path.
+//!
+//! Strategies can use this to decide between two speed run paths, or whether more exploration
+//! pays off before the next speed run.
+
+use crate::{
+    bot::{path_actions, Action},
+    maze::{Cell, Pose},
+    stats::{RunStats, Totals},
+};
+
+/// The weights of an estimated score formula. Lower scores are better.
+///
+/// `score = best run effective distance + turn weight * best run turns
+///        + total weight * (total effective distance + turn weight * total turns)`
+///
+/// The defaults are an estimate, not constants taken from mms: a turn costs as much as a cell and
+/// the whole session counts a tenth as much as the best run. Compare [`ScoreModel::score`] with
+/// the `Score` mms reports and set the weights to match the simulator in use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreModel {
+    /// How many cells a single turn is worth. Default = `1.0`, an estimate
+    pub turn_weight: f32,
+    /// How much the whole session counts compared to the best run. Default = `0.1`, an estimate
+    pub total_weight: f32,
+}
+
+impl Default for ScoreModel {
+    fn default() -> Self {
+        Self {
+            turn_weight: 1.0,
+            total_weight: 0.1,
+        }
+    }
+}
+
+/// The predicted stats after driving a candidate path
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Prediction {
+    /// The totals over the whole session, including the candidate
+    pub total: Totals,
+    /// The best run, which is the candidate if it beats the previous best run
+    pub best_run: Totals,
+    pub total_effective_distance: f32,
+    pub best_run_effective_distance: f32,
+    pub score: f32,
+}
+
+impl ScoreModel {
+    #[allow(clippy::cast_precision_loss)]
+    fn cost(&self, totals: &Totals) -> f32 {
+        totals.effective_distance + self.turn_weight * totals.turns as f32
+    }
+
+    /// The score for the given best run and session totals
+    #[must_use]
+    pub fn score(&self, best_run: &Totals, total: &Totals) -> f32 {
+        self.cost(best_run) + self.total_weight * self.cost(total)
+    }
+
+    /// Predicts the stats after driving the candidate run, given the totals so far, which
+    /// include the exploration, and the best run so far
+    #[must_use]
+    pub fn predict(
+        &self,
+        total: &Totals,
+        best_run: Option<&Totals>,
+        candidate: &Totals,
+    ) -> Prediction {
+        let total = total.plus(candidate);
+        let best_run = match best_run {
+            Some(best) if self.cost(best) <= self.cost(candidate) => *best,
+            _ => *candidate,
+        };
+        Prediction {
+            total,
+            best_run,
+            total_effective_distance: total.effective_distance,
+            best_run_effective_distance: best_run.effective_distance,
+            score: self.score(&best_run, &total),
+        }
+    }
+
+    /// Predicts the stats after driving the path from the pose, starting from the given stats,
+    /// as returned by [`RunStats::fetch`] or [`MmsApi::tracked_stats`](crate::MmsApi::tracked_stats)
+    #[must_use]
+    pub fn predict_path(&self, stats: &RunStats, pose: &Pose, path: &[Cell]) -> Prediction {
+        self.predict(
+            &stats.total(),
+            stats.best_run().as_ref(),
+            &path_totals(pose, path),
+        )
+    }
+
+    /// Returns `true` if exploring further pays off: driving `improved` after the extra
+    /// exploration leads to a lower score than driving `current` right away
+    #[must_use]
+    pub fn exploration_pays_off(
+        &self,
+        stats: &RunStats,
+        current: &Totals,
+        exploration: &Totals,
+        improved: &Totals,
+    ) -> bool {
+        let best_run = stats.best_run();
+        let now = self.predict(&stats.total(), best_run.as_ref(), current);
+        let later = self.predict(
+            &stats.total().plus(exploration),
+            best_run.as_ref(),
+            improved,
+        );
+        later.score < now.score
+    }
+}
+
+/// The distance, turns and effective distance of driving the path from the pose with
+/// [`path_actions`]
+#[must_use]
+pub fn path_totals(pose: &Pose, path: &[Cell]) -> Totals {
+    path_actions(pose, path)
+        .into_iter()
+        .fold(Totals::ZERO, |mut totals, action| {
+            match action {
+                Action::MoveForward(distance) => totals.add_move(distance.get()),
+                Action::TurnLeft | Action::TurnRight => totals.turns += 1,
+                Action::Stop => {}
+            }
+            totals
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Direction;
+
+    fn totals(distance: i32, turns: i32, effective_distance: f32) -> Totals {
+        Totals {
+            distance,
+            turns,
+            effective_distance,
+        }
+    }
+
+    #[test]
+    fn score_is_best_run_plus_a_tenth_of_the_total() {
+        let model = ScoreModel::default();
+        for (best, total, expected) in [
+            (totals(0, 0, 0.0), totals(0, 0, 0.0), 0.0),
+            (totals(14, 2, 9.0), totals(14, 2, 9.0), 12.1),
+            (totals(14, 2, 9.0), totals(120, 60, 100.0), 27.0),
+        ] {
+            let score = model.score(&best, &total);
+            assert!((score - expected).abs() < 1e-4, "{score} != {expected}");
+        }
+    }
+
+    #[test]
+    fn path_totals_merge_straight_moves() {
+        let path = [
+            Cell::new(0, 0),
+            Cell::new(0, 1),
+            Cell::new(0, 2),
+            Cell::new(0, 3),
+            Cell::new(1, 3),
+        ];
+        // move 3 (2.5 effective), turn right, move 1
+        assert_eq!(path_totals(&Pose::start(), &path), totals(4, 1, 3.5));
+        // turning around first takes two turns
+        let south = Pose::new(Cell::new(0, 0), Direction::South);
+        assert_eq!(path_totals(&south, &path), totals(4, 3, 3.5));
+        assert_eq!(path_totals(&Pose::start(), &path[..1]), Totals::ZERO);
+    }
+
+    #[test]
+    fn predict_keeps_the_cheaper_best_run() {
+        let model = ScoreModel::default();
+        let explored = totals(60, 30, 50.0);
+        let best = totals(10, 4, 7.0);
+
+        let worse = model.predict(&explored, Some(&best), &totals(12, 6, 8.0));
+        assert_eq!(worse.best_run, best);
+        assert_eq!(worse.total, totals(72, 36, 58.0));
+        assert!((worse.score - (11.0 + 9.4)).abs() < 1e-4);
+
+        let better = model.predict(&explored, Some(&best), &totals(8, 2, 6.0));
+        assert_eq!(better.best_run, totals(8, 2, 6.0));
+        assert_eq!(
+            model.predict(&explored, None, &worse.best_run).best_run,
+            best
+        );
+    }
+
+    #[test]
+    fn exploration_pays_off_only_for_large_gains() {
+        let model = ScoreModel::default();
+        let stats = RunStats {
+            total_distance: Some(60),
+            total_turns: Some(30),
+            total_effective_distance: Some(50.0),
+            ..RunStats::default()
+        };
+        let current = totals(20, 10, 15.0);
+        let exploration = totals(10, 5, 8.0);
+        // saves 10 on the best run, adds 0.3 through the total
+        assert!(model.exploration_pays_off(&stats, &current, &exploration, &totals(12, 4, 11.0)));
+        // saves 1 on the best run, adds 1.2 through the total
+        assert!(!model.exploration_pays_off(&stats, &current, &exploration, &totals(19, 10, 14.0)));
+    }
+}
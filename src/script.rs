@@ -0,0 +1,347 @@
+//! Bots written as [rhai](https://rhai.rs) scripts, run without recompiling.
+//!
+//! The script gets the [`MmsApi`](crate::MmsApi) commands as plain functions, e.g. `move_forward()` or
+//! `set_color(0, 0, CellColor::Red)`, the `Direction`, `CellColor` and `StatQuery` enums as
+//! modules of constants, and `get_stat(query)` and `stats()` for the stats. A failed command
+//! throws, so scripts can `try` and `catch` it. `stdout` belongs to mms, so `print` and `debug`
+//! write to `stderr`.
+
+use std::{cell::RefCell, num::NonZeroU32, path::Path, rc::Rc};
+
+use rhai::{Dynamic, Engine, EvalAltResult, Map, Module, FLOAT, INT};
+
+use crate::{
+    connection::{LineTransport, Stdio},
+    stdio, CellColor, Connection, Direction, MmsError, Stat, StatQuery,
+};
+
+type ScriptResult<T> = Result<T, Box<EvalAltResult>>;
+
+/// Turns the result of a [`Connection`] call into a script result, which the script can `catch`
+fn script<T>(result: Result<T, MmsError>) -> ScriptResult<T> {
+    result.map_err(|error| error.to_string().into())
+}
+
+/// The connection the commands of a script go to
+trait Shared<T>: Clone + 'static {
+    fn call<R>(
+        &self,
+        command: impl FnOnce(&mut Connection<T>) -> Result<R, MmsError>,
+    ) -> ScriptResult<R>;
+}
+
+/// The global connection of [`MmsApi`](crate::MmsApi)
+#[derive(Clone, Copy)]
+struct Global;
+
+impl Shared<Stdio> for Global {
+    fn call<R>(
+        &self,
+        command: impl FnOnce(&mut Connection<Stdio>) -> Result<R, MmsError>,
+    ) -> ScriptResult<R> {
+        script(command(&mut stdio()))
+    }
+}
+
+impl<T: 'static> Shared<T> for Rc<RefCell<Connection<T>>> {
+    fn call<R>(
+        &self,
+        command: impl FnOnce(&mut Connection<T>) -> Result<R, MmsError>,
+    ) -> ScriptResult<R> {
+        script(command(&mut self.borrow_mut()))
+    }
+}
+
+/// A cell coordinate or distance, which scripts pass as a signed integer
+fn unsigned(value: INT) -> ScriptResult<u32> {
+    u32::try_from(value)
+        .map_err(|_| format!("{value} is not a valid coordinate or distance").into())
+}
+
+/// A distance to move forward, which has to be at least one cell
+fn distance(value: INT) -> ScriptResult<NonZeroU32> {
+    NonZeroU32::new(unsigned(value)?).ok_or_else(|| "the distance has to be at least 1".into())
+}
+
+/// The value of a stat, an integer for distances and turns, a float for effective distances
+/// and the score and `()` while mms has no value yet
+fn stat_value(stat: Stat) -> Dynamic {
+    match (stat.int_value(), stat.float_value()) {
+        (Some(value), _) => Dynamic::from(INT::from(value)),
+        (None, Some(value)) => Dynamic::from(FLOAT::from(value)),
+        (None, None) => Dynamic::UNIT,
+    }
+}
+
+/// Registers `T` under `name`, with `==`, `!=` and `to_string`, and its variants as constants of
+/// a module of the same name
+fn register_enum<T>(engine: &mut Engine, name: &str, variants: &[T])
+where
+    T: Clone + Copy + PartialEq + std::fmt::Debug + Send + Sync + 'static,
+{
+    engine
+        .register_type_with_name::<T>(name)
+        .register_fn("==", |a: T, b: T| a == b)
+        .register_fn("!=", |a: T, b: T| a != b)
+        .register_fn("to_string", |value: &mut T| format!("{value:?}"))
+        .register_fn("to_debug", |value: &mut T| format!("{value:?}"));
+    let mut module = Module::new();
+    for &variant in variants {
+        module.set_var(format!("{variant:?}"), variant);
+    }
+    engine.register_static_module(name, module.into());
+}
+
+/// A script engine with the mms api registered, talking to mms over `stdin` and `stdout` like
+/// [`MmsApi`](crate::MmsApi)
+#[must_use]
+pub fn engine() -> Engine {
+    let mut engine = base_engine();
+    register_api(&mut engine, Global);
+    engine
+}
+
+/// A script engine with the mms api registered against `connection`, e.g. to run a script
+/// in-process. Keep a clone of `connection` to reach the transport once the script is done.
+#[must_use]
+pub fn engine_with<T: LineTransport + 'static>(connection: Rc<RefCell<Connection<T>>>) -> Engine {
+    let mut engine = base_engine();
+    register_api(&mut engine, connection);
+    engine
+}
+
+/// An engine with the enums registered and `print` and `debug` going to `stderr`
+fn base_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .on_print(|text| eprintln!("{text}"))
+        .on_debug(|text, source, position| match source {
+            Some(source) => eprintln!("{source} @ {position:?} | {text}"),
+            None => eprintln!("{position:?} | {text}"),
+        });
+
+    register_enum(&mut engine, "Direction", &Direction::ALL);
+    register_enum(&mut engine, "CellColor", &CellColor::ALL);
+    register_enum(&mut engine, "StatQuery", &StatQuery::ALL);
+    engine
+        .register_fn("left", Direction::left)
+        .register_fn("right", Direction::right)
+        .register_fn("opposite", Direction::opposite);
+    engine
+}
+
+/// Registers the commands as functions, which send them over `connection`
+fn register_api<T: LineTransport + 'static>(engine: &mut Engine, connection: impl Shared<T>) {
+    let c = connection;
+    engine
+        .register_fn("maze_width", {
+            let c = c.clone();
+            move || c.call(Connection::maze_width).map(INT::from)
+        })
+        .register_fn("maze_height", {
+            let c = c.clone();
+            move || c.call(Connection::maze_height).map(INT::from)
+        })
+        .register_fn("wall_front", {
+            let c = c.clone();
+            move || c.call(Connection::wall_front)
+        })
+        .register_fn("wall_right", {
+            let c = c.clone();
+            move || c.call(Connection::wall_right)
+        })
+        .register_fn("wall_left", {
+            let c = c.clone();
+            move || c.call(Connection::wall_left)
+        })
+        .register_fn("move_forward", {
+            let c = c.clone();
+            move || c.call(|c| c.move_forward(None))
+        })
+        .register_fn("move_forward", {
+            let c = c.clone();
+            move |cells: INT| {
+                let cells = distance(cells)?;
+                c.call(|c| c.move_forward(Some(cells)))
+            }
+        })
+        .register_fn("turn_right", {
+            let c = c.clone();
+            move || c.call(Connection::turn_right)
+        })
+        .register_fn("turn_left", {
+            let c = c.clone();
+            move || c.call(Connection::turn_left)
+        })
+        .register_fn("set_wall", {
+            let c = c.clone();
+            move |x: INT, y: INT, direction: Direction| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.set_wall(x, y, &direction))
+            }
+        })
+        .register_fn("clear_wall", {
+            let c = c.clone();
+            move |x: INT, y: INT, direction: Direction| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.clear_wall(x, y, &direction))
+            }
+        })
+        .register_fn("set_color", {
+            let c = c.clone();
+            move |x: INT, y: INT, color: CellColor| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.set_color(x, y, &color))
+            }
+        })
+        .register_fn("clear_color", {
+            let c = c.clone();
+            move |x: INT, y: INT| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.clear_color(x, y))
+            }
+        })
+        .register_fn("clear_all_color", {
+            let c = c.clone();
+            move || c.call(Connection::clear_all_color)
+        })
+        .register_fn("set_text", {
+            let c = c.clone();
+            move |x: INT, y: INT, text: &str| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.set_text(x, y, text))
+            }
+        })
+        .register_fn("clear_text", {
+            let c = c.clone();
+            move |x: INT, y: INT| {
+                let (x, y) = (unsigned(x)?, unsigned(y)?);
+                c.call(|c| c.clear_text(x, y))
+            }
+        })
+        .register_fn("clear_all_text", {
+            let c = c.clone();
+            move || c.call(Connection::clear_all_text)
+        })
+        .register_fn("was_reset", {
+            let c = c.clone();
+            move || c.call(Connection::was_reset)
+        })
+        .register_fn("ack_reset", {
+            let c = c.clone();
+            move || c.call(Connection::ack_reset)
+        })
+        .register_fn("get_stat", {
+            let c = c.clone();
+            move |query: StatQuery| c.call(|c| c.get_stat(&query)).map(stat_value)
+        })
+        .register_fn("stats", move || {
+            let mut stats = Map::new();
+            for query in StatQuery::ALL {
+                let stat = c.call(|c| c.get_stat(&query))?;
+                stats.insert(format!("{query:?}").into(), stat_value(stat));
+            }
+            ScriptResult::Ok(stats)
+        });
+}
+
+/// Runs the script at `path` with the mms api registered
+///
+/// # Errors
+/// If the script cannot be read or parsed, or throws an error that it does not catch
+pub fn run_file(path: &Path) -> ScriptResult<()> {
+    engine().run_file(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::Streams;
+
+    type TestConnection = Rc<RefCell<Connection<Streams<&'static [u8], Vec<u8>>>>>;
+
+    /// A connection that answers with `responses`
+    fn connection(responses: &'static str) -> TestConnection {
+        Rc::new(RefCell::new(Connection::new(Streams::new(
+            responses.as_bytes(),
+            Vec::new(),
+        ))))
+    }
+
+    /// The lines the script sent
+    fn sent(connection: &TestConnection) -> String {
+        let connection = connection.borrow();
+        String::from_utf8(connection.transport().get_ref().1.clone()).unwrap()
+    }
+
+    #[test]
+    fn enums_round_trip_through_scripts() {
+        let connection = connection("");
+        let engine = engine_with(connection.clone());
+        let name: String = engine
+            .eval(
+                "let direction = Direction::North.right();
+                 if direction != Direction::East || direction.opposite() != Direction::West {
+                     throw \"wrong direction\";
+                 }
+                 set_color(1, 2, CellColor::DarkGreen);
+                 set_wall(0, 0, direction);
+                 direction.to_string()",
+            )
+            .unwrap();
+        assert_eq!(name, "East");
+        let color: CellColor = engine.eval("CellColor::Red").unwrap();
+        assert_eq!(color, CellColor::Red);
+        assert_eq!(sent(&connection), "setColor 1 2 G\nsetWall 0 0 e\n");
+    }
+
+    #[test]
+    fn answers_are_returned_to_the_script() {
+        let connection = connection("16\ntrue\nack\n42\n");
+        let engine = engine_with(connection.clone());
+        let result: INT = engine
+            .eval(
+                "let width = maze_width();
+                 if !wall_front() { throw \"no wall\"; }
+                 move_forward(3);
+                 width + get_stat(StatQuery::TotalTurns)",
+            )
+            .unwrap();
+        assert_eq!(result, 58);
+        assert_eq!(
+            sent(&connection),
+            "mazeWidth\nwallFront\nmoveForward 3\ngetStat total-turns\n"
+        );
+    }
+
+    /// Runs `command` in a `try` block and returns the error it caught
+    fn caught(engine: &Engine, command: &str) -> String {
+        engine
+            .eval(&format!(
+                "let caught = \"\"; try {{ {command}; }} catch (error) {{ caught = error; }} caught"
+            ))
+            .unwrap()
+    }
+
+    #[test]
+    fn failed_commands_can_be_caught() {
+        let connection = connection("crash\n");
+        let engine = engine_with(connection.clone());
+        assert_eq!(
+            caught(&engine, "move_forward(0)"),
+            "the distance has to be at least 1"
+        );
+        assert_eq!(sent(&connection), "");
+        assert!(caught(&engine, "turn_left()").contains("crash"));
+        assert_eq!(sent(&connection), "turnLeft\n");
+        assert!(engine.run("move_forward(-1);").is_err());
+    }
+
+    #[test]
+    fn the_global_engine_rejects_a_zero_distance() {
+        assert_eq!(
+            caught(&engine(), "move_forward(0)"),
+            "the distance has to be at least 1"
+        );
+    }
+}
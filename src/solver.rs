@@ -0,0 +1,255 @@
+//! Flood fill and shortest path planning on top of the maze knowledge
+
+use std::collections::VecDeque;
+
+use crate::{
+    grid::Grid,
+    maze::{Cell, Maze, Pose, WallState},
+    Direction,
+};
+
+/// How walls that were never sensed are treated while planning
+/// cbindgen:prefix-with-name
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "python",
+    pyo3::pyclass(eq, eq_int, hash, frozen, from_py_object)
+)]
+pub enum Assumption {
+    /// Unknown walls are treated as open. Gives a lower bound for the real distance.
+    Optimistic,
+    /// Unknown walls are treated as walls. Only uses paths that are known to exist.
+    Pessimistic,
+}
+
+impl Assumption {
+    /// Both assumptions
+    pub const ALL: [Assumption; 2] = [Assumption::Optimistic, Assumption::Pessimistic];
+
+    /// Returns `true` if a wall in the given state can be passed under this assumption
+    #[must_use]
+    pub fn is_passable(self, wall: WallState) -> bool {
+        match wall {
+            WallState::Open => true,
+            WallState::Unknown => self == Assumption::Optimistic,
+            WallState::Wall => false,
+        }
+    }
+}
+
+impl TryFrom<i32> for Assumption {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|&assumption| assumption as i32 == value)
+            .ok_or(value)
+    }
+}
+
+/// Returns the number of cells to the nearest target for every cell, or `None` for cells that
+/// cannot reach any target
+#[must_use]
+pub fn flood(maze: &Maze, targets: &[Cell], assumption: Assumption) -> Grid<Option<u32>> {
+    let mut distances = Grid::new(maze.width(), maze.height(), None);
+    let mut queue = VecDeque::new();
+    for &target in targets {
+        if let Some(distance @ None) = distances.get_mut(target) {
+            *distance = Some(0);
+            queue.push_back(target);
+        }
+    }
+    while let Some(cell) = queue.pop_front() {
+        let next = distances[cell].map(|d| d + 1);
+        for direction in Direction::ALL {
+            if !assumption.is_passable(maze.wall(cell, direction)) {
+                continue;
+            }
+            if let Some(neighbor) = maze.neighbor(cell, direction) {
+                if distances[neighbor].is_none() {
+                    distances[neighbor] = next;
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+    distances
+}
+
+/// The direction of the first step from `pose` downhill on a flood map. Keeps the current
+/// heading on ties, to avoid needless turns.
+#[must_use]
+pub fn next_step(
+    maze: &Maze,
+    distances: &Grid<Option<u32>>,
+    pose: &Pose,
+    assumption: Assumption,
+) -> Option<Direction> {
+    let current = distances.get(pose.cell).copied().flatten()?;
+    let preferred = [
+        pose.heading,
+        pose.heading.left(),
+        pose.heading.right(),
+        pose.heading.opposite(),
+    ];
+    preferred
+        .into_iter()
+        .filter(|&direction| assumption.is_passable(maze.wall(pose.cell, direction)))
+        .filter_map(|direction| {
+            let neighbor = maze.neighbor(pose.cell, direction)?;
+            let distance = distances[neighbor]?;
+            (distance < current).then_some((distance, direction))
+        })
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, direction)| direction)
+}
+
+/// The shortest path from `pose` to any of the targets, including the start and the final cell.
+///
+/// Among paths of equal length the one that keeps going straight for longest is preferred.
+#[must_use]
+pub fn shortest_path(
+    maze: &Maze,
+    pose: &Pose,
+    targets: &[Cell],
+    assumption: Assumption,
+) -> Option<Vec<Cell>> {
+    let distances = flood(maze, targets, assumption);
+    distances.get(pose.cell).copied().flatten()?;
+    let mut pose = *pose;
+    let mut path = vec![pose.cell];
+    while distances[pose.cell] != Some(0) {
+        let direction = next_step(maze, &distances, &pose, assumption)?;
+        pose.heading = direction;
+        pose.move_forward(1);
+        path.push(pose.cell);
+    }
+    Some(path)
+}
+
+/// The direction of every step along a path of adjacent cells
+#[must_use]
+pub fn path_directions(path: &[Cell]) -> Vec<Direction> {
+    path.windows(2)
+        .filter_map(|pair| {
+            Direction::ALL
+                .into_iter()
+                .find(|&direction| pair[0].step(direction) == Some(pair[1]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x3 maze with every wall known and open
+    fn open_maze() -> Maze {
+        let mut maze = Maze::new(3, 3);
+        for cell in maze.clone().cells() {
+            for direction in Direction::ALL {
+                maze.set_wall(cell, direction, WallState::Open);
+            }
+        }
+        maze
+    }
+
+    #[test]
+    fn flood_counts_cells_to_the_target() {
+        let maze = open_maze();
+        let distances = flood(&maze, &[Cell::new(2, 2)], Assumption::Pessimistic);
+        assert_eq!(distances[Cell::new(2, 2)], Some(0));
+        assert_eq!(distances[Cell::new(0, 0)], Some(4));
+        assert_eq!(distances[Cell::new(1, 2)], Some(1));
+    }
+
+    #[test]
+    fn unknown_walls_depend_on_the_assumption() {
+        let maze = Maze::new(3, 3);
+        let target = [Cell::new(2, 2)];
+        assert_eq!(
+            flood(&maze, &target, Assumption::Optimistic)[maze.start()],
+            Some(4)
+        );
+        assert_eq!(
+            flood(&maze, &target, Assumption::Pessimistic)[maze.start()],
+            None
+        );
+    }
+
+    #[test]
+    fn walls_lengthen_the_path() {
+        let mut maze = open_maze();
+        maze.set_wall(Cell::new(0, 0), Direction::East, WallState::Wall);
+        maze.set_wall(Cell::new(0, 1), Direction::East, WallState::Wall);
+        let path = shortest_path(
+            &maze,
+            &Pose::start(),
+            &[Cell::new(1, 0)],
+            Assumption::Pessimistic,
+        )
+        .unwrap();
+        assert_eq!(path.first(), Some(&Cell::new(0, 0)));
+        assert_eq!(path.last(), Some(&Cell::new(1, 0)));
+        assert_eq!(
+            path_directions(&path),
+            [
+                Direction::North,
+                Direction::North,
+                Direction::East,
+                Direction::South,
+                Direction::South,
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_targets_have_no_path() {
+        let mut maze = open_maze();
+        for direction in Direction::ALL {
+            maze.set_wall(Cell::new(1, 1), direction, WallState::Wall);
+        }
+        let distances = flood(&maze, &[Cell::new(1, 1)], Assumption::Optimistic);
+        assert_eq!(distances[maze.start()], None);
+        assert_eq!(
+            next_step(&maze, &distances, &Pose::start(), Assumption::Optimistic),
+            None
+        );
+        assert_eq!(
+            shortest_path(
+                &maze,
+                &Pose::start(),
+                &[Cell::new(1, 1)],
+                Assumption::Optimistic
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn next_step_keeps_the_heading_on_ties() {
+        let maze = open_maze();
+        let distances = flood(&maze, &[Cell::new(2, 2)], Assumption::Pessimistic);
+        let north = Pose::start();
+        let east = Pose::new(maze.start(), Direction::East);
+        let step = |pose| next_step(&maze, &distances, &pose, Assumption::Pessimistic);
+        assert_eq!(step(north), Some(Direction::North));
+        assert_eq!(step(east), Some(Direction::East));
+    }
+
+    #[test]
+    fn path_at_the_target_is_a_single_cell() {
+        let maze = open_maze();
+        assert_eq!(
+            shortest_path(
+                &maze,
+                &Pose::start(),
+                &[maze.start()],
+                Assumption::Optimistic
+            ),
+            Some(vec![maze.start()])
+        );
+    }
+}
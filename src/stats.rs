@@ -0,0 +1,531 @@
+//! All mms stats in a single snapshot, and a local tracker to cross-check them
+
+use std::fmt;
+
+use crate::{
+    connection::{Connection, LineTransport},
+    maze::{Cell, Maze, Pose},
+    MmsApi, MmsError, ResultType, Stat, StatQuery,
+};
+
+/// The values of all ten stats at one point in time. `None` means mms has no value yet.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunStats {
+    pub total_distance: Option<i32>,
+    pub total_turns: Option<i32>,
+    pub best_run_distance: Option<i32>,
+    pub best_run_turns: Option<i32>,
+    pub current_run_distance: Option<i32>,
+    pub current_run_turns: Option<i32>,
+    pub total_effective_distance: Option<f32>,
+    pub best_run_effective_distance: Option<f32>,
+    pub current_run_effective_distance: Option<f32>,
+    pub score: Option<f32>,
+}
+
+impl RunStats {
+    /// Queries all stats from mms
+    ///
+    /// # Errors
+    /// `IoError`
+    /// `ParseIntError`
+    /// `ParseFloatError`
+    /// # Panics
+    /// this panics when `use_panics` is disabled
+    pub fn fetch() -> ResultType<Self> {
+        let mut stats = Self::default();
+        for query in StatQuery::ALL {
+            stats.set(try_api!(MmsApi::get_stat(&query)));
+        }
+        return_result!(stats);
+    }
+
+    /// Like [`RunStats::fetch`], but over the given connection, which always returns a `Result`
+    ///
+    /// # Errors
+    /// `IoError`
+    /// `ParseIntError`
+    /// `ParseFloatError`
+    pub fn fetch_from<T: LineTransport>(connection: &mut Connection<T>) -> Result<Self, MmsError> {
+        let mut stats = Self::default();
+        for query in StatQuery::ALL {
+            stats.set(connection.get_stat(&query)?);
+        }
+        Ok(stats)
+    }
+
+    /// Stores the value of the stat in the matching field
+    pub fn set(&mut self, stat: Stat) {
+        match stat.query() {
+            StatQuery::TotalDistance => self.total_distance = stat.int_value(),
+            StatQuery::TotalTurns => self.total_turns = stat.int_value(),
+            StatQuery::BestRunDistance => self.best_run_distance = stat.int_value(),
+            StatQuery::BestRunTurns => self.best_run_turns = stat.int_value(),
+            StatQuery::CurrentRunDistance => self.current_run_distance = stat.int_value(),
+            StatQuery::CurrentRunTurns => self.current_run_turns = stat.int_value(),
+            StatQuery::TotalEffectiveDistance => {
+                self.total_effective_distance = stat.float_value();
+            }
+            StatQuery::BestRunEffectiveDistance => {
+                self.best_run_effective_distance = stat.float_value();
+            }
+            StatQuery::CurrentRunEffectiveDistance => {
+                self.current_run_effective_distance = stat.float_value();
+            }
+            StatQuery::Score => self.score = stat.float_value(),
+        }
+    }
+
+    /// The stat for the query, with `-1` for missing values like mms reports them
+    #[must_use]
+    pub fn get(&self, query: StatQuery) -> Stat {
+        let int = |value: Option<i32>| value.unwrap_or(-1);
+        let float = |value: Option<f32>| value.unwrap_or(-1.0);
+        match query {
+            StatQuery::TotalDistance => Stat::TotalDistance(int(self.total_distance)),
+            StatQuery::TotalTurns => Stat::TotalTurns(int(self.total_turns)),
+            StatQuery::BestRunDistance => Stat::BestRunDistance(int(self.best_run_distance)),
+            StatQuery::BestRunTurns => Stat::BestRunTurns(int(self.best_run_turns)),
+            StatQuery::CurrentRunDistance => {
+                Stat::CurrentRunDistance(int(self.current_run_distance))
+            }
+            StatQuery::CurrentRunTurns => Stat::CurrentRunTurns(int(self.current_run_turns)),
+            StatQuery::TotalEffectiveDistance => {
+                Stat::TotalEffectiveDistance(float(self.total_effective_distance))
+            }
+            StatQuery::BestRunEffectiveDistance => {
+                Stat::BestRunEffectiveDistance(float(self.best_run_effective_distance))
+            }
+            StatQuery::CurrentRunEffectiveDistance => {
+                Stat::CurrentRunEffectiveDistance(float(self.current_run_effective_distance))
+            }
+            StatQuery::Score => Stat::Score(float(self.score)),
+        }
+    }
+
+    /// All ten stats, in the order of [`StatQuery::ALL`]
+    #[must_use]
+    pub fn stats(&self) -> [Stat; 10] {
+        StatQuery::ALL.map(|query| self.get(query))
+    }
+
+    /// The totals over the whole session, with missing values counted as zero
+    #[must_use]
+    pub fn total(&self) -> Totals {
+        Totals {
+            distance: self.total_distance.unwrap_or(0),
+            turns: self.total_turns.unwrap_or(0),
+            effective_distance: self.total_effective_distance.unwrap_or(0.0),
+        }
+    }
+
+    /// The best run, or `None` if no run was finished yet
+    #[must_use]
+    pub fn best_run(&self) -> Option<Totals> {
+        Some(Totals {
+            distance: self.best_run_distance?,
+            turns: self.best_run_turns?,
+            effective_distance: self.best_run_effective_distance?,
+        })
+    }
+
+    /// The stats whose values differ between the two snapshots
+    #[must_use]
+    pub fn changed(&self, other: &RunStats) -> Vec<StatQuery> {
+        StatQuery::ALL
+            .into_iter()
+            .filter(|&query| self.get(query) != other.get(query))
+            .collect()
+    }
+}
+
+impl FromIterator<Stat> for RunStats {
+    fn from_iter<I: IntoIterator<Item = Stat>>(stats: I) -> Self {
+        let mut run_stats = Self::default();
+        for stat in stats {
+            run_stats.set(stat);
+        }
+        run_stats
+    }
+}
+
+/// The effective distance mms counts for a single `move_forward` over the given number of
+/// cells. The first two cells count fully, every further cell counts half.
+#[must_use]
+pub fn effective_distance(distance: u32) -> f32 {
+    #[allow(clippy::cast_precision_loss)]
+    let distance = distance as f32;
+    if distance > 2.0 {
+        2.0 + (distance - 2.0) / 2.0
+    } else {
+        distance
+    }
+}
+
+/// Distance, turns and effective distance of a run or of the whole session
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Totals {
+    pub distance: i32,
+    pub turns: i32,
+    pub effective_distance: f32,
+}
+
+impl Totals {
+    pub(crate) const ZERO: Totals = Totals {
+        distance: 0,
+        turns: 0,
+        effective_distance: 0.0,
+    };
+
+    pub(crate) fn add_move(&mut self, distance: u32) {
+        self.distance = self
+            .distance
+            .saturating_add(i32::try_from(distance).unwrap_or(i32::MAX));
+        self.effective_distance += effective_distance(distance);
+    }
+
+    /// Sums up the values of both
+    #[must_use]
+    pub fn plus(&self, other: &Totals) -> Totals {
+        Totals {
+            distance: self.distance.saturating_add(other.distance),
+            turns: self.turns.saturating_add(other.turns),
+            effective_distance: self.effective_distance + other.effective_distance,
+        }
+    }
+
+    /// The cost mms uses to pick the best run: effective distance plus turns
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn cost(&self) -> f32 {
+        self.effective_distance + self.turns as f32
+    }
+}
+
+/// Computes the stats locally from the commands sent to mms.
+///
+/// A run starts whenever the mouse leaves the start cell and ends when a `move_forward` ends in
+/// a goal cell. A finished run replaces the best run if it has a lower
+/// [cost](Totals::cost). A reset discards the current run and moves the mouse back to the start.
+/// Runs are only detected once the maze size is known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatsTracker {
+    width: Option<u32>,
+    height: Option<u32>,
+    goals: Vec<Cell>,
+    pose: Pose,
+    total: Totals,
+    current: Option<Totals>,
+    best: Option<Totals>,
+    in_run: bool,
+}
+
+impl Default for StatsTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatsTracker {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            width: None,
+            height: None,
+            goals: Vec::new(),
+            pose: Pose::start(),
+            total: Totals::ZERO,
+            current: None,
+            best: None,
+            in_run: false,
+        }
+    }
+
+    /// Records the answer to `maze_width`
+    pub fn set_width(&mut self, width: i32) {
+        self.width = u32::try_from(width).ok();
+        self.update_goals();
+    }
+
+    /// Records the answer to `maze_height`
+    pub fn set_height(&mut self, height: i32) {
+        self.height = u32::try_from(height).ok();
+        self.update_goals();
+    }
+
+    fn update_goals(&mut self) {
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            self.goals = Maze::new(width, height).goals();
+        }
+    }
+
+    /// Where the mouse should be according to the commands sent so far
+    #[must_use]
+    pub fn pose(&self) -> Pose {
+        self.pose
+    }
+
+    /// Records a successful `move_forward` over the given number of cells
+    pub fn record_move(&mut self, distance: u32) {
+        if self.pose.cell == Pose::start().cell && !self.goals.is_empty() {
+            self.in_run = true;
+            self.current = Some(Totals::ZERO);
+        }
+        self.pose.move_forward(distance);
+        self.total.add_move(distance);
+        if self.in_run {
+            let current = self.current.get_or_insert(Totals::ZERO);
+            current.add_move(distance);
+            if self.goals.contains(&self.pose.cell) {
+                let current = *current;
+                if self.best.is_none_or(|best| current.cost() < best.cost()) {
+                    self.best = Some(current);
+                }
+                self.in_run = false;
+            }
+        }
+    }
+
+    /// Records a successful `turn_left`
+    pub fn record_turn_left(&mut self) {
+        self.pose.turn_left();
+        self.record_turn();
+    }
+
+    /// Records a successful `turn_right`
+    pub fn record_turn_right(&mut self) {
+        self.pose.turn_right();
+        self.record_turn();
+    }
+
+    fn record_turn(&mut self) {
+        self.total.turns = self.total.turns.saturating_add(1);
+        if self.in_run {
+            let current = self.current.get_or_insert(Totals::ZERO);
+            current.turns = current.turns.saturating_add(1);
+        }
+    }
+
+    /// Records a successful `ack_reset`
+    pub fn record_reset(&mut self) {
+        self.pose = Pose::start();
+        self.in_run = false;
+        self.current = None;
+    }
+
+    /// The totals over all commands
+    #[must_use]
+    pub fn total(&self) -> Totals {
+        self.total
+    }
+
+    /// The current or last finished run, if any
+    #[must_use]
+    pub fn current_run(&self) -> Option<Totals> {
+        self.current
+    }
+
+    /// The best finished run, if any
+    #[must_use]
+    pub fn best_run(&self) -> Option<Totals> {
+        self.best
+    }
+
+    /// The tracked stats in the same shape as the reported ones. The score is left empty.
+    #[must_use]
+    pub fn stats(&self) -> RunStats {
+        RunStats {
+            total_distance: Some(self.total.distance),
+            total_turns: Some(self.total.turns),
+            best_run_distance: self.best.map(|run| run.distance),
+            best_run_turns: self.best.map(|run| run.turns),
+            current_run_distance: self.current.map(|run| run.distance),
+            current_run_turns: self.current.map(|run| run.turns),
+            total_effective_distance: Some(self.total.effective_distance),
+            best_run_effective_distance: self.best.map(|run| run.effective_distance),
+            current_run_effective_distance: self.current.map(|run| run.effective_distance),
+            score: None,
+        }
+    }
+
+    /// Every tracked stat, except the score, that differs from the reported value
+    #[must_use]
+    pub fn check(&self, reported: &RunStats) -> Vec<StatDiscrepancy> {
+        let tracked = self.stats();
+        StatQuery::ALL
+            .into_iter()
+            .map(|query| StatDiscrepancy {
+                query,
+                tracked: tracked.get(query),
+                reported: reported.get(query),
+            })
+            .filter(|discrepancy| {
+                let StatDiscrepancy {
+                    tracked, reported, ..
+                } = discrepancy;
+                match discrepancy.query {
+                    StatQuery::TotalDistance
+                    | StatQuery::TotalTurns
+                    | StatQuery::BestRunDistance
+                    | StatQuery::BestRunTurns
+                    | StatQuery::CurrentRunDistance
+                    | StatQuery::CurrentRunTurns => tracked.int_value() != reported.int_value(),
+                    StatQuery::TotalEffectiveDistance
+                    | StatQuery::BestRunEffectiveDistance
+                    | StatQuery::CurrentRunEffectiveDistance => {
+                        match (tracked.float_value(), reported.float_value()) {
+                            (Some(a), Some(b)) => (a - b).abs() > 1e-3,
+                            (a, b) => a.is_some() != b.is_some(),
+                        }
+                    }
+                    // the tracker can not compute the score
+                    StatQuery::Score => false,
+                }
+            })
+            .collect()
+    }
+}
+
+/// A stat whose locally tracked value differs from the one reported by mms
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatDiscrepancy {
+    pub query: StatQuery,
+    pub tracked: Stat,
+    pub reported: Stat,
+}
+
+impl fmt::Display for StatDiscrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = |stat: Stat| {
+            stat.value()
+                .map_or_else(|| "none".to_string(), |v| v.to_string())
+        };
+        write!(
+            f,
+            "{}: tracked {}, reported {}",
+            self.query.get_string(),
+            value(self.tracked),
+            value(self.reported)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker_with_run() -> StatsTracker {
+        let mut tracker = StatsTracker::new();
+        tracker.set_width(4);
+        tracker.set_height(4);
+        tracker.record_move(1);
+        tracker.record_turn_right();
+        tracker.record_move(1);
+        tracker
+    }
+
+    #[test]
+    fn tracker_finishes_a_run_in_the_goal() {
+        let tracker = tracker_with_run();
+        let run = Totals {
+            distance: 2,
+            turns: 1,
+            effective_distance: 2.0,
+        };
+        assert_eq!(tracker.best_run(), Some(run));
+        assert_eq!(tracker.current_run(), Some(run));
+        assert_eq!(tracker.total(), run);
+    }
+
+    #[test]
+    fn check_reports_every_differing_stat_but_the_score() {
+        let tracker = tracker_with_run();
+        let mut reported = tracker.stats();
+        reported.score = Some(3.3);
+        assert!(tracker.check(&reported).is_empty());
+
+        reported.total_turns = Some(2);
+        reported.best_run_effective_distance = Some(2.5);
+        reported.current_run_distance = None;
+        let queries: Vec<StatQuery> = tracker
+            .check(&reported)
+            .into_iter()
+            .map(|discrepancy| discrepancy.query)
+            .collect();
+        assert_eq!(
+            queries,
+            [
+                StatQuery::TotalTurns,
+                StatQuery::CurrentRunDistance,
+                StatQuery::BestRunEffectiveDistance
+            ]
+        );
+    }
+
+    #[test]
+    fn effective_distance_counts_cells_after_the_second_half() {
+        for (distance, expected) in [
+            (0, 0.0),
+            (1, 1.0),
+            (2, 2.0),
+            (3, 2.5),
+            (4, 3.0),
+            (7, 4.5),
+            (15, 8.5),
+        ] {
+            assert_eq!(effective_distance(distance), expected, "{distance} cells");
+        }
+    }
+
+    #[test]
+    fn totals_add_up_moves_separately() {
+        let mut totals = Totals::ZERO;
+        totals.add_move(5);
+        totals.add_move(1);
+        totals.turns = 2;
+        assert_eq!(totals.distance, 6);
+        assert_eq!(totals.effective_distance, 4.5);
+        assert_eq!(totals.cost(), 6.5);
+        assert_eq!(totals.plus(&totals).cost(), 13.0);
+    }
+
+    #[test]
+    fn run_stats_treat_minus_one_as_missing() {
+        let stats: RunStats = [
+            Stat::TotalDistance(12),
+            Stat::TotalTurns(4),
+            Stat::TotalEffectiveDistance(9.5),
+            Stat::BestRunDistance(-1),
+            Stat::BestRunTurns(-1),
+            Stat::BestRunEffectiveDistance(-1.0),
+            Stat::Score(-1.0),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(stats.total_distance, Some(12));
+        assert_eq!(stats.best_run_distance, None);
+        assert_eq!(stats.score, None);
+        assert_eq!(stats.best_run(), None);
+        assert_eq!(
+            stats.total(),
+            Totals {
+                distance: 12,
+                turns: 4,
+                effective_distance: 9.5
+            }
+        );
+        assert_eq!(stats.get(StatQuery::Score), Stat::Score(-1.0));
+        assert_eq!(stats.stats()[0], Stat::TotalDistance(12));
+        assert_eq!(stats.changed(&stats), []);
+        assert_eq!(
+            stats.changed(&RunStats::default()),
+            [
+                StatQuery::TotalDistance,
+                StatQuery::TotalTurns,
+                StatQuery::TotalEffectiveDistance
+            ]
+        );
+    }
+}
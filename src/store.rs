@@ -0,0 +1,293 @@
+//! Saving maze knowledge to disk, so a restarted bot process can reuse previous exploration.
+//!
+//! Every maze is stored in its own file named after the maze size and its
+//! [fingerprint](Maze::fingerprint), e.g. `maze-16x16-3f2a9c0d11e4b576.txt`. The file format is
+//! plain text with one line per row, starting at `y = 0`. Every cell is written as its north,
+//! east, south and west wall (`#` wall, `.` open, `?` unknown) followed by `v` if it was
+//! visited or `-` if not.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    maze::{Cell, Maze, WallState},
+    Direction, MmsError,
+};
+
+const HEADER: &str = "mms-maze 1";
+
+/// The largest width and height a maze file may have, far above any real maze
+const MAX_SIZE: u32 = 256;
+
+/// A directory holding saved mazes
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MazeStore {
+    dir: PathBuf,
+}
+
+impl MazeStore {
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn prefix(width: u32, height: u32) -> String {
+        format!("maze-{width}x{height}-")
+    }
+
+    /// The file the maze is saved to
+    #[must_use]
+    pub fn path_for(&self, maze: &Maze) -> PathBuf {
+        self.dir.join(format!(
+            "{}{:016x}.txt",
+            Self::prefix(maze.width(), maze.height()),
+            maze.fingerprint()
+        ))
+    }
+
+    /// The files of all saved mazes with the given size
+    ///
+    /// # Errors
+    /// `IoError`
+    pub fn saved(&self, width: u32, height: u32) -> Result<Vec<PathBuf>, MmsError> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let prefix = Self::prefix(width, height);
+        let mut paths = Vec::new();
+        for entry in entries {
+            let path = entry?.path();
+            if path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with(&prefix) && name.ends_with(".txt"))
+            {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Saves the maze and removes older saves of the same maze, i.e. files of the same size
+    /// that agree with it and know fewer walls. Returns the path of the new file.
+    ///
+    /// # Errors
+    /// `IoError`
+    pub fn save(&self, maze: &Maze) -> Result<PathBuf, MmsError> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path_for(maze);
+        fs::write(&path, to_string(maze))?;
+        for old in self.saved(maze.width(), maze.height())? {
+            if old == path {
+                continue;
+            }
+            if let Ok(saved) = load(&old) {
+                if saved.agrees_with(maze) && saved.known_walls() <= maze.known_walls() {
+                    fs::remove_file(old)?;
+                }
+            }
+        }
+        Ok(path)
+    }
+
+    /// Finds the saved maze that agrees with everything observed so far. If several do, the one
+    /// with the most known walls wins. Files that can not be read are skipped.
+    ///
+    /// # Errors
+    /// `IoError`
+    pub fn find(&self, observed: &Maze) -> Result<Option<Maze>, MmsError> {
+        Ok(self
+            .saved(observed.width(), observed.height())?
+            .iter()
+            .filter_map(|path| load(path).ok())
+            .filter(|saved| saved.agrees_with(observed))
+            .max_by_key(Maze::known_walls))
+    }
+}
+
+/// Loads a maze saved with [`MazeStore::save`]
+///
+/// # Errors
+/// `IoError`
+/// `InvalidMazeFile`
+pub fn load(path: &Path) -> Result<Maze, MmsError> {
+    from_str(&fs::read_to_string(path)?)
+}
+
+/// Writes the maze in the store file format
+#[must_use]
+pub fn to_string(maze: &Maze) -> String {
+    let mut out = format!(
+        "{HEADER}\n{} {}\n{:016x}\n",
+        maze.width(),
+        maze.height(),
+        maze.fingerprint()
+    );
+    for y in 0..maze.height() {
+        let row: Vec<String> = (0..maze.width())
+            .map(|x| {
+                let cell = Cell::new(x, y);
+                let mut token: String = maze
+                    .walls(cell)
+                    .iter()
+                    .map(|wall| match wall {
+                        WallState::Unknown => '?',
+                        WallState::Open => '.',
+                        WallState::Wall => '#',
+                    })
+                    .collect();
+                token.push(if maze.is_visited(cell) { 'v' } else { '-' });
+                token
+            })
+            .collect();
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+    out
+}
+
+/// Reads a maze from the store file format
+///
+/// # Errors
+/// `InvalidMazeFile`
+pub fn from_str(s: &str) -> Result<Maze, MmsError> {
+    let invalid = |reason: &str| MmsError::InvalidMazeFile(reason.to_string());
+    let mut lines = s.lines();
+    if lines.next() != Some(HEADER) {
+        return Err(invalid("missing header"));
+    }
+    let size = lines.next().ok_or_else(|| invalid("missing size"))?;
+    let (width, height) = size
+        .split_once(' ')
+        .ok_or_else(|| invalid("invalid size"))?;
+    let parse_size = |size: &str| {
+        size.parse::<u32>()
+            .ok()
+            .filter(|size| (1..=MAX_SIZE).contains(size))
+            .ok_or_else(|| invalid("invalid size"))
+    };
+    let mut maze = Maze::new(parse_size(width)?, parse_size(height)?);
+    let fingerprint = lines.next().ok_or_else(|| invalid("missing fingerprint"))?;
+
+    for y in 0..maze.height() {
+        let row = lines.next().ok_or_else(|| invalid("missing row"))?;
+        let tokens: Vec<&str> = row.split(' ').collect();
+        if tokens.len() != maze.width() as usize {
+            return Err(invalid("wrong number of cells in row"));
+        }
+        for (x, token) in (0..).zip(tokens) {
+            let cell = Cell::new(x, y);
+            let chars: Vec<char> = token.chars().collect();
+            let [north, east, south, west, visited] = chars[..] else {
+                return Err(invalid("cells need five characters"));
+            };
+            for (direction, c) in Direction::ALL.into_iter().zip([north, east, south, west]) {
+                let wall = match c {
+                    '?' => WallState::Unknown,
+                    '.' => WallState::Open,
+                    '#' => WallState::Wall,
+                    _ => return Err(invalid("unknown wall character")),
+                };
+                let known = maze.wall(cell, direction);
+                if known.is_known() && wall.is_known() && known != wall {
+                    return Err(invalid("walls of neighbouring cells disagree"));
+                }
+                if wall.is_known() {
+                    maze.set_wall(cell, direction, wall);
+                }
+            }
+            if visited == 'v' {
+                maze.mark_visited(cell);
+            }
+        }
+    }
+
+    if u64::from_str_radix(fingerprint, 16).ok() != Some(maze.fingerprint()) {
+        return Err(invalid("fingerprint does not match the walls"));
+    }
+    Ok(maze)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Pose;
+
+    fn explored_maze() -> Maze {
+        let mut maze = Maze::new(4, 3);
+        maze.record_sensors(&Pose::start(), false, true, true);
+        maze.record_sensors(
+            &Pose::new(Cell::new(0, 1), Direction::North),
+            true,
+            true,
+            false,
+        );
+        maze
+    }
+
+    #[test]
+    fn round_trip() {
+        let maze = explored_maze();
+        assert_eq!(from_str(&to_string(&maze)).unwrap(), maze);
+        let empty = Maze::new(1, 1);
+        assert_eq!(from_str(&to_string(&empty)).unwrap(), empty);
+    }
+
+    #[test]
+    fn malformed_files_are_invalid() {
+        let valid = to_string(&explored_maze());
+        let invalid = [
+            String::new(),
+            valid.replacen(HEADER, "mms-maze 2", 1),
+            valid.replacen("4 3", "4", 1),
+            valid.replacen("4 3", "four 3", 1),
+            valid.replacen("4 3", "-4 3", 1),
+            valid.replacen("4 3", "0 3", 1),
+            valid.replacen("4 3", "4 99999999999", 1),
+            valid.replacen("4 3", "4 4294967295", 1),
+            valid.replacen("4 3", "257 3", 1),
+            valid.replacen("4 3", "5 3", 1),
+            valid.replacen('v', "x", 1).replacen('#', "x", 1),
+            valid.replacen('#', ".", 1),
+            valid.lines().take(4).collect::<Vec<_>>().join("\n"),
+        ];
+        for file in invalid {
+            assert!(
+                matches!(from_str(&file), Err(MmsError::InvalidMazeFile(_))),
+                "{file:?} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn find_only_returns_agreeing_mazes() {
+        let dir = std::env::temp_dir().join(format!("mms-rs-store-{}", std::process::id()));
+        let store = MazeStore::new(&dir);
+        let saved = explored_maze();
+        store.save(&saved).unwrap();
+
+        let mut observed = Maze::new(4, 3);
+        observed.record_sensors(&Pose::start(), false, true, true);
+        assert_eq!(store.find(&observed).unwrap(), Some(saved));
+
+        observed.record_sensors(
+            &Pose::new(Cell::new(0, 1), Direction::North),
+            false,
+            true,
+            false,
+        );
+        assert_eq!(store.find(&observed).unwrap(), None);
+        assert_eq!(store.find(&Maze::new(3, 4)).unwrap(), None);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
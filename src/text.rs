@@ -0,0 +1,323 @@
+//! Formatting numbers into cell texts, which mms limits to ten characters
+
+/// The maximum number of characters mms displays in a cell
+pub const MAX_TEXT_LEN: usize = 10;
+
+/// How integers are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NumberMode {
+    #[default]
+    Decimal,
+    /// Lower case hexadecimal without prefix
+    Hex,
+}
+
+/// Rules for fitting numbers into cell texts
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextFormat {
+    /// The maximum number of characters. Default = `MAX_TEXT_LEN`
+    pub width: usize,
+    /// Default = `Decimal`
+    pub mode: NumberMode,
+    /// Shorten large decimal numbers with a `k`, `M`, `G` or `T` suffix instead of clamping
+    /// them. Default = `true`
+    pub abbreviate: bool,
+    /// The maximum number of decimals for floats. Default = `1`
+    pub precision: usize,
+}
+
+impl Default for TextFormat {
+    fn default() -> Self {
+        Self {
+            width: MAX_TEXT_LEN,
+            mode: NumberMode::Decimal,
+            abbreviate: true,
+            precision: 1,
+        }
+    }
+}
+
+impl TextFormat {
+    /// Formats an integer. Numbers that do not fit are abbreviated, if enabled, or clamped to
+    /// the largest number that fits, marked with a leading `>` or `<`, e.g. `>999999999`. If not
+    /// even that fits, the text is filled with `#`.
+    #[must_use]
+    pub fn int(&self, value: i64) -> String {
+        let text = match self.mode {
+            NumberMode::Decimal => value.to_string(),
+            NumberMode::Hex if value < 0 => format!("-{:x}", value.unsigned_abs()),
+            NumberMode::Hex => format!("{value:x}"),
+        };
+        if text.len() <= self.width {
+            return text;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        if let Some(text) = self.abbreviated(value as f64) {
+            return text;
+        }
+        self.clamped(value < 0)
+    }
+
+    /// Formats a float with at most `precision` decimals, dropping decimals until it fits.
+    /// Larger numbers are abbreviated, if enabled, or clamped like [`TextFormat::int`].
+    #[must_use]
+    pub fn float(&self, value: f64) -> String {
+        if value.is_nan() {
+            return fit("nan", self.width);
+        }
+        if value.is_infinite() {
+            return fit(if value < 0.0 { "-inf" } else { "inf" }, self.width);
+        }
+        if self.mode == NumberMode::Decimal {
+            for precision in (0..=self.precision).rev() {
+                let text = format!("{value:.precision$}");
+                if text.len() <= self.width {
+                    return text;
+                }
+            }
+        }
+        let rounded = value.round();
+        #[allow(clippy::cast_precision_loss)]
+        if (i64::MIN as f64..i64::MAX as f64).contains(&rounded) {
+            #[allow(clippy::cast_possible_truncation)]
+            return self.int(rounded as i64);
+        }
+        self.abbreviated(value)
+            .unwrap_or_else(|| self.clamped(value < 0.0))
+    }
+
+    /// Formats two integers separated by `|`, e.g. `12|3`. If they do not fit, the available
+    /// width is split between them. Below three characters the text is filled with `#`.
+    #[must_use]
+    pub fn pair(&self, a: i64, b: i64) -> String {
+        let text = format!("{}|{}", self.int(a), self.int(b));
+        if text.len() <= self.width {
+            return text;
+        }
+        if self.width < 3 {
+            return overflow(self.width);
+        }
+        let available = self.width.saturating_sub(1);
+        let left = Self {
+            width: available - available / 2,
+            ..*self
+        };
+        let right = Self {
+            width: available / 2,
+            ..*self
+        };
+        fit(&format!("{}|{}", left.int(a), right.int(b)), self.width)
+    }
+
+    fn abbreviated(&self, value: f64) -> Option<String> {
+        if !self.abbreviate || self.mode != NumberMode::Decimal {
+            return None;
+        }
+        [(1e3, 'k'), (1e6, 'M'), (1e9, 'G'), (1e12, 'T')]
+            .into_iter()
+            .flat_map(|(divisor, suffix)| {
+                let scaled = value / divisor;
+                [
+                    format!("{scaled:.1}{suffix}"),
+                    format!("{scaled:.0}{suffix}"),
+                ]
+            })
+            .find(|text| {
+                text.len() <= self.width && !text.starts_with("0") && !text.starts_with("-0")
+            })
+    }
+
+    fn clamped(&self, negative: bool) -> String {
+        let digit = match self.mode {
+            NumberMode::Decimal => "9",
+            NumberMode::Hex => "f",
+        };
+        let prefix = if negative { "<-" } else { ">" };
+        match self.width.checked_sub(prefix.len()) {
+            Some(digits) if digits > 0 => format!("{prefix}{}", digit.repeat(digits)),
+            _ => overflow(self.width),
+        }
+    }
+}
+
+/// The text for a value that does not fit at all
+fn overflow(width: usize) -> String {
+    "#".repeat(width)
+}
+
+/// Cuts the text down to at most `width` characters
+#[must_use]
+pub fn fit(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Values that can be written into a cell
+pub trait CellText {
+    /// The text for the value, fitted with the given format
+    fn cell_text(&self, format: &TextFormat) -> String;
+}
+
+macro_rules! int_cell_text {
+    ($($t: ty),*) => {$(
+        impl CellText for $t {
+            fn cell_text(&self, format: &TextFormat) -> String {
+                format.int(i64::from(*self))
+            }
+        }
+    )*};
+}
+
+int_cell_text!(i8, i16, i32, i64, u8, u16, u32);
+
+impl CellText for u64 {
+    #[allow(clippy::cast_precision_loss)]
+    fn cell_text(&self, format: &TextFormat) -> String {
+        i64::try_from(*self).map_or_else(|_| format.float(*self as f64), |value| format.int(value))
+    }
+}
+
+impl CellText for usize {
+    #[allow(clippy::cast_precision_loss)]
+    fn cell_text(&self, format: &TextFormat) -> String {
+        i64::try_from(*self).map_or_else(|_| format.float(*self as f64), |value| format.int(value))
+    }
+}
+
+impl CellText for f32 {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        format.float(f64::from(*self))
+    }
+}
+
+impl CellText for f64 {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        format.float(*self)
+    }
+}
+
+impl CellText for str {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        fit(self, format.width)
+    }
+}
+
+impl CellText for String {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        fit(self, format.width)
+    }
+}
+
+impl<A: Copy + Into<i64>, B: Copy + Into<i64>> CellText for (A, B) {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        format.pair(self.0.into(), self.1.into())
+    }
+}
+
+/// `None` is written as an empty text, which clears the cell
+impl<T: CellText> CellText for Option<T> {
+    fn cell_text(&self, format: &TextFormat) -> String {
+        self.as_ref()
+            .map(|value| value.cell_text(format))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn width(width: usize) -> TextFormat {
+        TextFormat {
+            width,
+            ..TextFormat::default()
+        }
+    }
+
+    #[test]
+    fn ints_that_fit_are_unchanged() {
+        let format = TextFormat::default();
+        assert_eq!(format.int(0), "0");
+        assert_eq!(format.int(9_999_999_999), "9999999999");
+        assert_eq!(format.int(-123_456_789), "-123456789");
+    }
+
+    #[test]
+    fn large_numbers_are_abbreviated() {
+        let format = TextFormat::default();
+        assert_eq!(format.int(12_345_678_901), "12345679k");
+        assert_eq!(format.int(i64::MAX), "9223372.0T");
+        assert_eq!(format.int(i64::MIN), "-9223372T");
+        assert_eq!(width(3).int(1500), "2k");
+        assert_eq!(width(3).int(999_500), "1M");
+    }
+
+    #[test]
+    fn floats_beyond_i64_do_not_saturate() {
+        let format = TextFormat::default();
+        assert_eq!(format.float(1e20), "100000000T");
+        assert_eq!(format.float(-1e19), "-10000000T");
+        assert_eq!(format.float(1e30), ">999999999");
+        assert_eq!(format.float(-1e30), "<-99999999");
+        assert_eq!(format.float(f64::NAN), "nan");
+        assert_eq!(width(2).float(f64::NEG_INFINITY), "-i");
+    }
+
+    #[test]
+    fn floats_drop_decimals_to_fit() {
+        let format = TextFormat::default();
+        assert_eq!(format.float(0.25), "0.2");
+        assert_eq!(format.float(1_234_567.25), "1234567.2");
+        assert_eq!(format.float(123_456_789.5), "123456790");
+    }
+
+    #[test]
+    fn clamped_numbers_are_marked() {
+        let clamp = TextFormat {
+            abbreviate: false,
+            ..width(4)
+        };
+        assert_eq!(clamp.int(12_345), ">999");
+        assert_eq!(clamp.int(-12_345), "<-99");
+        assert_eq!(width(3).int(-99_999), "<-9");
+
+        let hex = TextFormat {
+            mode: NumberMode::Hex,
+            ..TextFormat::default()
+        };
+        assert_eq!(hex.int(255), "ff");
+        assert_eq!(hex.int(-255), "-ff");
+        assert_eq!(hex.int(i64::MAX), ">fffffffff");
+        assert_eq!(hex.int(i64::MIN), "<-ffffffff");
+    }
+
+    #[test]
+    fn too_narrow_texts_are_filled() {
+        assert_eq!(width(1).int(7), "7");
+        assert_eq!(width(1).int(-5), "#");
+        assert_eq!(width(2).int(-5), "-5");
+        assert_eq!(width(2).int(-50), "##");
+        assert_eq!(width(0).int(1), "");
+    }
+
+    #[test]
+    fn pairs_share_the_width() {
+        let format = TextFormat::default();
+        assert_eq!(format.pair(12, 3), "12|3");
+        assert_eq!(format.pair(123_456, 789_012), "123k|789k");
+        assert_eq!(format.pair(-9_999_999, -9_999_999), "-10M|-10M");
+        assert_eq!(width(3).pair(1, 2), "1|2");
+        assert_eq!(width(3).pair(10, 2), "#|2");
+        assert_eq!(width(2).pair(1, 2), "##");
+        assert_eq!(width(1).pair(1, 2), "#");
+    }
+
+    #[test]
+    fn cell_texts() {
+        let format = TextFormat::default();
+        assert_eq!(Some(42_u8).cell_text(&format), "42");
+        assert_eq!(None::<u8>.cell_text(&format), "");
+        assert_eq!(u64::MAX.cell_text(&format), "18446744T");
+        assert_eq!("a long cell text".cell_text(&format), "a long cel");
+        assert_eq!((1_i32, 2_u8).cell_text(&format), "1|2");
+    }
+}
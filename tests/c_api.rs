@@ -0,0 +1,412 @@
+//! Round trips through the C ABI of the typed buffers, declared here the way a C caller sees them
+
+#![cfg(feature = "c_api_internal")]
+
+use std::{
+    ffi::{c_char, c_void},
+    ptr, slice,
+};
+
+use mms_rs::{
+    maze::{Cell, Maze, Pose},
+    solver::{self, Assumption},
+    CellColor, StatQuery,
+};
+
+#[repr(C)]
+struct ByteBuffer {
+    ptr: *mut u8,
+    length: i32,
+    capacity: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct MmsPose {
+    cell: Cell,
+    heading: i32,
+}
+
+impl From<Pose> for MmsPose {
+    fn from(pose: Pose) -> Self {
+        Self {
+            cell: pose.cell,
+            heading: pose.heading as i32,
+        }
+    }
+}
+
+#[repr(C)]
+struct CellBuffer {
+    ptr: *mut Cell,
+    length: i32,
+    capacity: i32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+struct StatValue {
+    query: StatQuery,
+    kind: i32,
+    has_value: bool,
+    int_value: i32,
+    float_value: f32,
+}
+
+#[repr(C)]
+struct StatValueBuffer {
+    ptr: *mut StatValue,
+    length: i32,
+    capacity: i32,
+}
+
+enum MazeHandle {}
+enum SolverHandle {}
+enum MmsHandle {}
+
+type ReadLineCallback = Option<extern "C" fn(*mut c_void, *mut u8, i32) -> i32>;
+type WriteLineCallback = Option<extern "C" fn(*mut c_void, *const u8, i32) -> i32>;
+
+extern "C" {
+    fn mms_maze_new(width: u32, height: u32) -> *mut MazeHandle;
+    fn mms_maze_free(maze: *mut MazeHandle);
+    fn mms_maze_goals(maze: *mut MazeHandle, goals: *mut *mut CellBuffer) -> i32;
+    fn mms_solver_new(assumption: i32) -> *mut SolverHandle;
+    fn mms_solver_free(solver: *mut SolverHandle);
+    fn mms_solver_assumption(solver: *mut SolverHandle, assumption: *mut Assumption) -> i32;
+    fn mms_solver_path(
+        solver: *mut SolverHandle,
+        maze: *mut MazeHandle,
+        pose: MmsPose,
+        targets: *const Cell,
+        targets_len: u32,
+        path: *mut *mut CellBuffer,
+    ) -> i32;
+    fn mms_handle_new_callbacks(
+        read_line: ReadLineCallback,
+        write_line: WriteLineCallback,
+        user_data: *mut c_void,
+    ) -> *mut MmsHandle;
+    fn mms_handle_free(handle: *mut MmsHandle);
+    fn mms_set_text(
+        handle: *mut MmsHandle,
+        x: u32,
+        y: u32,
+        text_utf8: *const u8,
+        text_len: i32,
+    ) -> i32;
+    fn mms_set_text_cstr(handle: *mut MmsHandle, x: u32, y: u32, text: *const c_char) -> i32;
+    fn mms_last_error_message() -> *mut ByteBuffer;
+    fn mms_free_byte_buffer(buffer: *mut ByteBuffer);
+    fn mms_get_stats(
+        handle: *mut MmsHandle,
+        queries: *const i32,
+        queries_len: i32,
+        stats: *mut *mut StatValueBuffer,
+    ) -> i32;
+    fn mms_set_wall(handle: *mut MmsHandle, x: u32, y: u32, direction: i32) -> i32;
+    fn mms_set_color(handle: *mut MmsHandle, x: u32, y: u32, color: i32) -> i32;
+    fn mms_parse_color(color_utf8: *const u8, color_len: i32, color: *mut CellColor) -> i32;
+    fn mms_get_stat_text(handle: *mut MmsHandle, query: i32, value: *mut *mut ByteBuffer) -> i32;
+    fn mms_free_cell_buffer(buffer: *mut CellBuffer);
+    fn mms_free_stat_value_buffer(buffer: *mut StatValueBuffer);
+}
+
+const OK: i32 = 0;
+const INVALID_COLOR_STRING: i32 = 6;
+const INVALID_ARGUMENT: i32 = 9;
+
+unsafe fn cells(buffer: *mut CellBuffer) -> Vec<Cell> {
+    let buffer = &*buffer;
+    assert!(buffer.length <= buffer.capacity);
+    slice::from_raw_parts(buffer.ptr, buffer.length as usize).to_vec()
+}
+
+#[test]
+fn path_buffer_matches_shortest_path() {
+    let start = Pose::start();
+    let model = Maze::new(16, 16);
+    let expected =
+        solver::shortest_path(&model, &start, &model.goals(), Assumption::Optimistic).unwrap();
+    unsafe {
+        let maze = mms_maze_new(16, 16);
+        let solver = mms_solver_new(Assumption::Optimistic as i32);
+        let mut path = ptr::null_mut();
+        assert_eq!(
+            mms_solver_path(solver, maze, start.into(), ptr::null(), 0, &mut path),
+            OK
+        );
+        assert_eq!(cells(path), expected);
+        mms_free_cell_buffer(path);
+        mms_solver_free(solver);
+        mms_maze_free(maze);
+    }
+}
+
+#[test]
+fn path_buffer_is_empty_without_a_path() {
+    unsafe {
+        let maze = mms_maze_new(4, 4);
+        let solver = mms_solver_new(Assumption::Pessimistic as i32);
+        let mut path = ptr::null_mut();
+        assert_eq!(
+            mms_solver_path(
+                solver,
+                maze,
+                Pose::start().into(),
+                ptr::null(),
+                0,
+                &mut path
+            ),
+            OK
+        );
+        assert!(cells(path).is_empty());
+        mms_free_cell_buffer(path);
+        mms_solver_free(solver);
+        mms_maze_free(maze);
+    }
+}
+
+#[test]
+fn goal_buffer_holds_the_center_cells() {
+    unsafe {
+        let maze = mms_maze_new(16, 16);
+        let mut goals = ptr::null_mut();
+        assert_eq!(mms_maze_goals(maze, &mut goals), OK);
+        assert_eq!(cells(goals), Maze::new(16, 16).goals());
+        mms_free_cell_buffer(goals);
+        mms_maze_free(maze);
+    }
+}
+
+#[test]
+fn null_handles_and_buffers_are_rejected_or_ignored() {
+    unsafe {
+        let mut goals = ptr::null_mut();
+        assert_eq!(
+            mms_maze_goals(ptr::null_mut(), &mut goals),
+            INVALID_ARGUMENT
+        );
+        assert!(goals.is_null());
+        mms_free_cell_buffer(ptr::null_mut());
+        mms_free_stat_value_buffer(ptr::null_mut());
+    }
+}
+
+#[test]
+fn corrupted_buffers_are_freed_without_panicking() {
+    unsafe {
+        let maze = mms_maze_new(16, 16);
+        for (length, capacity) in [(-1, 4), (4, -1), (5, 4)] {
+            let mut goals = ptr::null_mut();
+            assert_eq!(mms_maze_goals(maze, &mut goals), OK);
+            (*goals).length = length;
+            (*goals).capacity = capacity;
+            mms_free_cell_buffer(goals);
+        }
+        mms_maze_free(maze);
+    }
+}
+
+/// Records every line and answers requests with the number of lines sent so far
+#[derive(Default)]
+struct FakeMms {
+    lines: Vec<String>,
+}
+
+extern "C" fn write_line(user_data: *mut c_void, line: *const u8, len: i32) -> i32 {
+    let mms = unsafe { &mut *user_data.cast::<FakeMms>() };
+    let line = unsafe { slice::from_raw_parts(line, len as usize) };
+    mms.lines.push(String::from_utf8(line.to_vec()).unwrap());
+    0
+}
+
+extern "C" fn read_line(user_data: *mut c_void, buffer: *mut u8, capacity: i32) -> i32 {
+    let mms = unsafe { &*user_data.cast::<FakeMms>() };
+    let response = format!("{}\n", mms.lines.len());
+    assert!(response.len() <= capacity as usize);
+    unsafe { ptr::copy_nonoverlapping(response.as_ptr(), buffer, response.len()) };
+    response.len() as i32
+}
+
+unsafe fn fake_handle(mms: &mut FakeMms) -> *mut MmsHandle {
+    mms_handle_new_callbacks(Some(read_line), Some(write_line), ptr::from_mut(mms).cast())
+}
+
+unsafe fn stat_values(buffer: *mut StatValueBuffer) -> Vec<StatValue> {
+    let buffer = &*buffer;
+    assert!(buffer.length <= buffer.capacity);
+    slice::from_raw_parts(buffer.ptr, buffer.length as usize).to_vec()
+}
+
+#[test]
+fn stat_buffer_keeps_the_query_order() {
+    let mut mms = FakeMms::default();
+    let queries = [StatQuery::Score as i32, StatQuery::TotalTurns as i32];
+    unsafe {
+        let handle = fake_handle(&mut mms);
+        let mut stats = ptr::null_mut();
+        assert_eq!(mms_get_stats(handle, queries.as_ptr(), 2, &mut stats), OK);
+        let values = stat_values(stats);
+        mms_free_stat_value_buffer(stats);
+
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].query, StatQuery::Score);
+        assert!(values[0].has_value);
+        assert!((values[0].float_value - 1.0).abs() < f32::EPSILON);
+        assert_eq!(values[1].query, StatQuery::TotalTurns);
+        assert_eq!(values[1].int_value, 2);
+
+        let mut all = ptr::null_mut();
+        assert_eq!(mms_get_stats(handle, ptr::null(), 0, &mut all), OK);
+        let values = stat_values(all);
+        mms_free_stat_value_buffer(all);
+        let order: Vec<_> = values.iter().map(|value| value.query).collect();
+        assert_eq!(order, StatQuery::ALL);
+        mms_handle_free(handle);
+    }
+}
+
+unsafe fn last_error_message() -> String {
+    let buffer = mms_last_error_message();
+    let message = slice::from_raw_parts((*buffer).ptr, (*buffer).length as usize).to_vec();
+    mms_free_byte_buffer(buffer);
+    String::from_utf8(message).unwrap()
+}
+
+#[test]
+fn strings_are_sent_with_and_without_length() {
+    let mut mms = FakeMms::default();
+    unsafe {
+        let handle = fake_handle(&mut mms);
+        assert_eq!(mms_set_text(handle, 1, 2, b"abc".as_ptr(), 2), OK);
+        assert_eq!(mms_set_text(handle, 1, 2, ptr::null(), 0), OK);
+        assert_eq!(mms_set_text_cstr(handle, 3, 4, c"hi".as_ptr()), OK);
+        mms_handle_free(handle);
+    }
+    assert_eq!(
+        mms.lines,
+        ["setText 1 2 ab", "setText 1 2 ", "setText 3 4 hi"]
+    );
+}
+
+#[test]
+fn invalid_strings_are_reported_and_not_sent() {
+    let mut mms = FakeMms::default();
+    unsafe {
+        let handle = fake_handle(&mut mms);
+
+        assert_eq!(mms_set_text(handle, 0, 0, ptr::null(), 3), INVALID_ARGUMENT);
+        assert!(last_error_message().contains("null"));
+
+        assert_eq!(
+            mms_set_text(handle, 0, 0, b"a".as_ptr(), -1),
+            INVALID_ARGUMENT
+        );
+        assert!(last_error_message().contains("negative"));
+
+        assert_eq!(
+            mms_set_text(handle, 0, 0, b"\xff".as_ptr(), 1),
+            INVALID_ARGUMENT
+        );
+        assert!(last_error_message().contains("UTF-8"));
+
+        assert_eq!(
+            mms_set_text_cstr(handle, 0, 0, c"\xff".as_ptr()),
+            INVALID_ARGUMENT
+        );
+        assert_eq!(
+            mms_set_text_cstr(handle, 0, 0, ptr::null()),
+            INVALID_ARGUMENT
+        );
+        mms_handle_free(handle);
+    }
+    assert!(mms.lines.is_empty());
+}
+
+#[test]
+fn invalid_enum_values_are_reported_and_not_sent() {
+    let mut mms = FakeMms::default();
+    unsafe {
+        let handle = fake_handle(&mut mms);
+
+        assert_eq!(mms_set_wall(handle, 0, 0, 4), INVALID_ARGUMENT);
+        assert!(last_error_message().contains("Direction"));
+        assert_eq!(mms_set_color(handle, 0, 0, -1), INVALID_ARGUMENT);
+        assert!(last_error_message().contains("CellColor"));
+
+        let mut text = ptr::null_mut();
+        assert_eq!(mms_get_stat_text(handle, 99, &mut text), INVALID_ARGUMENT);
+        assert!(text.is_null());
+        let mut stats = ptr::null_mut();
+        let queries = [StatQuery::Score as i32, 99];
+        assert_eq!(
+            mms_get_stats(handle, queries.as_ptr(), 2, &mut stats),
+            INVALID_ARGUMENT
+        );
+        assert!(stats.is_null());
+        assert!(last_error_message().contains("StatQuery"));
+        assert_eq!(
+            mms_get_stats(handle, queries.as_ptr(), -1, &mut stats),
+            INVALID_ARGUMENT
+        );
+        assert!(last_error_message().contains("negative"));
+        mms_handle_free(handle);
+
+        assert!(mms_solver_new(2).is_null());
+        assert!(last_error_message().contains("Assumption"));
+
+        let maze = mms_maze_new(4, 4);
+        let solver = mms_solver_new(Assumption::Optimistic as i32);
+        let pose = MmsPose {
+            cell: Cell::new(0, 0),
+            heading: 7,
+        };
+        let mut path = ptr::null_mut();
+        assert_eq!(
+            mms_solver_path(solver, maze, pose, ptr::null(), 0, &mut path),
+            INVALID_ARGUMENT
+        );
+        assert!(path.is_null());
+        mms_solver_free(solver);
+        mms_maze_free(maze);
+    }
+    assert!(mms.lines.is_empty());
+}
+
+#[test]
+fn typed_enums_are_written_through_out_parameters() {
+    unsafe {
+        let mut color = CellColor::Black;
+        assert_eq!(mms_parse_color(b"G".as_ptr(), 1, &mut color), OK);
+        assert_eq!(color, CellColor::DarkGreen);
+        assert_eq!(
+            mms_parse_color(b"x".as_ptr(), 1, &mut color),
+            INVALID_COLOR_STRING
+        );
+        assert!(last_error_message().starts_with("InvalidColorString"));
+        assert_eq!(color, CellColor::DarkGreen);
+
+        let solver = mms_solver_new(Assumption::Pessimistic as i32);
+        let mut assumption = Assumption::Optimistic;
+        assert_eq!(mms_solver_assumption(solver, &mut assumption), OK);
+        assert_eq!(assumption, Assumption::Pessimistic);
+        assert_eq!(
+            mms_solver_assumption(solver, ptr::null_mut()),
+            INVALID_ARGUMENT
+        );
+        mms_solver_free(solver);
+    }
+}
+
+#[test]
+fn null_callbacks_are_rejected() {
+    let mut mms = FakeMms::default();
+    unsafe {
+        let user_data = ptr::from_mut(&mut mms).cast();
+        assert!(mms_handle_new_callbacks(None, Some(write_line), user_data).is_null());
+        assert!(last_error_message().starts_with("InvalidArgument"));
+        assert!(mms_handle_new_callbacks(Some(read_line), None, user_data).is_null());
+        assert!(last_error_message().contains("null"));
+    }
+}
@@ -3,48 +3,524 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * The maximum number of characters mms displays in a cell
+ */
+#define MAX_TEXT_LEN 10
+
+/**
+ * The size of the buffer passed to a `ReadLineCallback`. mms never sends longer lines.
+ */
+#define READ_LINE_CAPACITY 256
+
+/**
+ * How walls that were never sensed are treated while planning
+ */
+enum Assumption {
+  /**
+   * Unknown walls are treated as open. Gives a lower bound for the real distance.
+   */
+  Assumption_Optimistic,
+  /**
+   * Unknown walls are treated as walls. Only uses paths that are known to exist.
+   */
+  Assumption_Pessimistic,
+};
+typedef int32_t Assumption;
+
+/**
+ * The cell color
+ */
+enum CellColor {
+  CellColor_Black,
+  CellColor_Blue,
+  CellColor_Gray,
+  CellColor_Cyan,
+  CellColor_Green,
+  CellColor_Orange,
+  CellColor_Red,
+  CellColor_White,
+  CellColor_Yellow,
+  CellColor_DarkBlue,
+  CellColor_DarkCyan,
+  CellColor_DarkGray,
+  CellColor_DarkGreen,
+  CellColor_DarkRed,
+  CellColor_DarkYellow,
+};
+typedef int32_t CellColor;
+
+/**
+ * The direction for the wall
+ */
+enum Direction {
+  Direction_North,
+  Direction_East,
+  Direction_South,
+  Direction_West,
+};
+typedef int32_t Direction;
+
+/**
+ * The result of every function of the C api. Everything except `Ok` means the call failed and
+ * `mms_last_error_message` describes why.
+ */
+enum MmsStatus {
+  MmsStatus_Ok = 0,
+  MmsStatus_ParseIntError,
+  MmsStatus_ParseFloatError,
+  MmsStatus_ParseStatQueryError,
+  MmsStatus_IoError,
+  MmsStatus_InvalidAck,
+  MmsStatus_InvalidColorString,
+  MmsStatus_InvalidDirectionString,
+  MmsStatus_InvalidMazeFile,
+  /**
+   * An argument was invalid, e.g. a null pointer, a negative length or an enum value out of
+   * range
+   */
+  MmsStatus_InvalidArgument,
+  /**
+   * The call panicked. The panic was caught before it could unwind into the caller.
+   */
+  MmsStatus_Panic,
+  MmsStatus_InvalidMazeSize,
+};
+typedef int32_t MmsStatus;
+
+/**
+ * Whether a stat is counted in whole cells and turns or is a float
+ */
+enum StatKind {
+  StatKind_Int,
+  StatKind_Float,
+};
+typedef int32_t StatKind;
+
+/**
+ * Which stat to query
+ */
+enum StatQuery {
+  StatQuery_TotalDistance,
+  StatQuery_TotalTurns,
+  StatQuery_BestRunDistance,
+  StatQuery_BestRunTurns,
+  StatQuery_CurrentRunDistance,
+  StatQuery_CurrentRunTurns,
+  StatQuery_TotalEffectiveDistance,
+  StatQuery_BestRunEffectiveDistance,
+  StatQuery_CurrentRunEffectiveDistance,
+  StatQuery_Score,
+};
+typedef int32_t StatQuery;
+
+/**
+ * What is known about a single wall
+ */
+enum WallState {
+  /**
+   * The wall was never sensed
+   */
+  WallState_Unknown,
+  /**
+   * There is no wall
+   */
+  WallState_Open,
+  /**
+   * There is a wall
+   */
+  WallState_Wall,
+};
+typedef int32_t WallState;
+
+/**
+ * The knowledge about the maze, see `mms_maze_new`
+ */
+typedef struct MazeHandle MazeHandle;
+
+/**
+ * A connection to mms, created with `mms_handle_new_stdio` or `mms_handle_new_callbacks` and
+ * freed with `mms_handle_free`. Every handle tracks its own stats.
+ */
+typedef struct MmsHandle MmsHandle;
+
+/**
+ * Tracks the pose of the mouse, see `mms_pose_new`
+ */
+typedef struct PoseHandle PoseHandle;
+
+/**
+ * Flood fill distances and path planning, see `mms_solver_new`
+ */
+typedef struct SolverHandle SolverHandle;
+
 typedef struct ByteBuffer {
   uint8_t *ptr;
   int32_t length;
   int32_t capacity;
 } ByteBuffer;
 
-int32_t maze_width(void);
+/**
+ * Writes the next line received from mms into `buffer`, without the line break, and returns its
+ * length in bytes. Returns a negative value if reading failed.
+ */
+typedef int32_t (*ReadLineCallback)(void *user_data, uint8_t *buffer, int32_t capacity);
+
+/**
+ * Sends the line, without the line break, to mms. Returns `0` on success and any other value if
+ * writing failed.
+ */
+typedef int32_t (*WriteLineCallback)(void *user_data, const uint8_t *line_utf8, int32_t line_len);
+
+/**
+ * The position of a cell. `(0, 0)` is the bottom left corner, `y` grows towards the north.
+ */
+typedef struct Cell {
+  uint32_t x;
+  uint32_t y;
+} Cell;
+
+/**
+ * An array of cells, free it with `mms_free_cell_buffer`. `length` and `capacity` count cells.
+ */
+typedef struct CellBuffer {
+  struct Cell *ptr;
+  int32_t length;
+  int32_t capacity;
+} CellBuffer;
+
+/**
+ * The value of a single stat
+ */
+typedef struct StatValue {
+  StatQuery query;
+  StatKind kind;
+  /**
+   * `false` while mms has no value yet, e.g. for the best run before the first run finished
+   */
+  bool has_value;
+  /**
+   * The value of integer stats. `-1` without a value.
+   */
+  int32_t int_value;
+  /**
+   * The value of float stats, and of integer stats converted to float. `-1` without a value.
+   */
+  float float_value;
+} StatValue;
+
+/**
+ * An array of stats, free it with `mms_free_stat_value_buffer`. `length` and `capacity` count
+ * stats.
+ */
+typedef struct StatValueBuffer {
+  struct StatValue *ptr;
+  int32_t length;
+  int32_t capacity;
+} StatValueBuffer;
+
+/**
+ * All stats at once, see `mms_get_all_stats`
+ */
+typedef struct RunStats {
+  struct StatValue total_distance;
+  struct StatValue total_turns;
+  struct StatValue best_run_distance;
+  struct StatValue best_run_turns;
+  struct StatValue current_run_distance;
+  struct StatValue current_run_turns;
+  struct StatValue total_effective_distance;
+  struct StatValue best_run_effective_distance;
+  struct StatValue current_run_effective_distance;
+  struct StatValue score;
+} RunStats;
+
+/**
+ * Where the mouse is and which way it is facing. `heading` is a `Direction`, which is checked
+ * before use.
+ */
+typedef struct MmsPose {
+  struct Cell cell;
+  int32_t heading;
+} MmsPose;
+
+/**
+ * The message of the last failed call on this thread, or null if no call failed yet. Free it
+ * with `mms_free_byte_buffer`.
+ */
+struct ByteBuffer *mms_last_error_message(void);
+
+/**
+ * Creates a handle that talks to mms over `stdin` and `stdout`
+ */
+struct MmsHandle *mms_handle_new_stdio(void);
+
+/**
+ * Creates a handle that talks to mms through the callbacks. `user_data` is passed to every call
+ * and has to stay valid until the handle is freed. Returns null if a callback is null.
+ */
+struct MmsHandle *mms_handle_new_callbacks(ReadLineCallback read_line,
+                                           WriteLineCallback write_line,
+                                           void *user_data);
+
+/**
+ * Frees a handle created by `mms_handle_new_stdio` or `mms_handle_new_callbacks`
+ */
+void mms_handle_free(struct MmsHandle *handle);
+
+MmsStatus mms_get_maze_width(struct MmsHandle *handle, int32_t *width);
+
+MmsStatus mms_get_maze_height(struct MmsHandle *handle, int32_t *height);
+
+MmsStatus mms_wall_front(struct MmsHandle *handle, bool *wall);
+
+MmsStatus mms_wall_right(struct MmsHandle *handle, bool *wall);
+
+MmsStatus mms_wall_left(struct MmsHandle *handle, bool *wall);
+
+MmsStatus mms_move_forward(struct MmsHandle *handle, uint32_t distance);
+
+MmsStatus mms_turn_right(struct MmsHandle *handle);
+
+MmsStatus mms_turn_left(struct MmsHandle *handle);
+
+/**
+ * `direction` is a `Direction`
+ */
+MmsStatus mms_set_wall(struct MmsHandle *handle, uint32_t x, uint32_t y, int32_t direction);
+
+/**
+ * Deprecated: use `mms_set_wall`, which takes a `Direction` instead of a string
+ */
+MmsStatus set_wall(struct MmsHandle *handle,
+                   uint32_t x,
+                   uint32_t y,
+                   const uint8_t *direction_utf8,
+                   int32_t direction_len);
+
+/**
+ * `direction` is a `Direction`
+ */
+MmsStatus mms_clear_wall(struct MmsHandle *handle, uint32_t x, uint32_t y, int32_t direction);
+
+/**
+ * Deprecated: use `mms_clear_wall`, which takes a `Direction` instead of a string
+ */
+MmsStatus clear_wall(struct MmsHandle *handle,
+                     uint32_t x,
+                     uint32_t y,
+                     const uint8_t *direction_utf8,
+                     int32_t direction_len);
+
+/**
+ * `color` is a `CellColor`
+ */
+MmsStatus mms_set_color(struct MmsHandle *handle, uint32_t x, uint32_t y, int32_t color);
+
+/**
+ * Deprecated: use `mms_set_color`, which takes a `CellColor` instead of a string
+ */
+MmsStatus set_color(struct MmsHandle *handle,
+                    uint32_t x,
+                    uint32_t y,
+                    const uint8_t *color_utf8,
+                    int32_t color_len);
+
+/**
+ * Parses the character mms uses for a color, e.g. `G` for `DarkGreen`, into `color`
+ */
+MmsStatus mms_parse_color(const uint8_t *color_utf8, int32_t color_len, CellColor *color);
+
+MmsStatus mms_clear_color(struct MmsHandle *handle, uint32_t x, uint32_t y);
+
+MmsStatus mms_clear_all_color(struct MmsHandle *handle);
+
+MmsStatus mms_set_text(struct MmsHandle *handle,
+                       uint32_t x,
+                       uint32_t y,
+                       const uint8_t *text_utf8,
+                       int32_t text_len);
+
+/**
+ * Like `mms_set_text`, but takes a NUL-terminated string
+ */
+MmsStatus mms_set_text_cstr(struct MmsHandle *handle, uint32_t x, uint32_t y, const char *text);
+
+MmsStatus mms_clear_text(struct MmsHandle *handle, uint32_t x, uint32_t y);
+
+MmsStatus mms_clear_all_text(struct MmsHandle *handle);
+
+MmsStatus mms_was_reset(struct MmsHandle *handle, bool *reset);
+
+MmsStatus mms_ack_reset(struct MmsHandle *handle);
+
+void mms_free_byte_buffer(struct ByteBuffer *buffer);
+
+void mms_free_cell_buffer(struct CellBuffer *buffer);
+
+void mms_free_stat_value_buffer(struct StatValueBuffer *buffer);
+
+/**
+ * Writes the stat, `query` is a `StatQuery`, as a string into `value`. Free it with
+ * `mms_free_byte_buffer`.
+ */
+MmsStatus mms_get_stat_text(struct MmsHandle *handle, int32_t query, struct ByteBuffer **value);
+
+/**
+ * Deprecated: use `mms_get_stat_text`, which takes a `StatQuery` instead of a string
+ */
+MmsStatus get_stat(struct MmsHandle *handle,
+                   const uint8_t *query_utf8,
+                   int32_t query_len,
+                   struct ByteBuffer **value);
+
+/**
+ * Writes the value of the stat, `query` is a `StatQuery`, into `value`, without any string
+ * parsing or freeing
+ */
+MmsStatus mms_get_stat(struct MmsHandle *handle, int32_t query, struct StatValue *value);
+
+/**
+ * Queries all stats and writes them into `stats`
+ */
+MmsStatus mms_get_all_stats(struct MmsHandle *handle, struct RunStats *stats);
+
+/**
+ * Queries the given stats, or all of them without queries, i.e. null or a length of `0`, and
+ * writes them into `stats` in the same order. `queries` are `StatQuery` values. A negative
+ * `queries_len` fails with `InvalidArgument`. Free `stats` with `mms_free_stat_value_buffer`.
+ */
+MmsStatus mms_get_stats(struct MmsHandle *handle,
+                        const int32_t *queries,
+                        int32_t queries_len,
+                        struct StatValueBuffer **stats);
+
+/**
+ * Creates an empty maze of the given size, with only the outer walls known
+ */
+struct MazeHandle *mms_maze_new(uint32_t width, uint32_t height);
+
+void mms_maze_free(struct MazeHandle *maze);
+
+MmsStatus mms_maze_width(struct MazeHandle *maze, uint32_t *width);
+
+MmsStatus mms_maze_height(struct MazeHandle *maze, uint32_t *height);
+
+/**
+ * Records a sensed wall on both of its sides
+ */
+MmsStatus mms_maze_record_wall(struct MazeHandle *maze,
+                               struct Cell cell,
+                               int32_t direction,
+                               bool present);
+
+/**
+ * Records the three sensor readings taken at the pose and marks its cell as visited
+ */
+MmsStatus mms_maze_record_sensors(struct MazeHandle *maze,
+                                  struct MmsPose pose,
+                                  bool front,
+                                  bool left,
+                                  bool right);
+
+MmsStatus mms_maze_wall(struct MazeHandle *maze,
+                        struct Cell cell,
+                        int32_t direction,
+                        WallState *wall);
 
-int32_t maze_height(void);
+MmsStatus mms_maze_mark_visited(struct MazeHandle *maze, struct Cell cell);
 
-bool wall_front(void);
+MmsStatus mms_maze_is_visited(struct MazeHandle *maze, struct Cell cell, bool *visited);
 
-bool wall_right(void);
+/**
+ * Writes the goal cells in the center of the maze into `goals`. Free it with
+ * `mms_free_cell_buffer`.
+ */
+MmsStatus mms_maze_goals(struct MazeHandle *maze, struct CellBuffer **goals);
 
-bool wall_left(void);
+MmsStatus mms_maze_is_goal(struct MazeHandle *maze, struct Cell cell, bool *goal);
 
-void move_forward(uint32_t distance);
+/**
+ * Creates a pose tracker at the start: `(0, 0)` facing north
+ */
+struct PoseHandle *mms_pose_new(void);
 
-void turn_right(void);
+void mms_pose_free(struct PoseHandle *tracker);
 
-void turn_left(void);
+MmsStatus mms_pose_get(struct PoseHandle *tracker, struct MmsPose *pose);
 
-void set_wall(uint32_t x, uint32_t y, const uint8_t *direction_utf8, int32_t direction_len);
+MmsStatus mms_pose_set(struct PoseHandle *tracker, struct MmsPose pose);
 
-void clear_wall(uint32_t x, uint32_t y, const uint8_t *direction_utf8, int32_t direction_len);
+MmsStatus mms_pose_turn_left(struct PoseHandle *tracker);
 
-void set_color(uint32_t x, uint32_t y, const uint8_t *color_utf8, int32_t color_len);
+MmsStatus mms_pose_turn_right(struct PoseHandle *tracker);
 
-void clear_color(uint32_t x, uint32_t y);
+/**
+ * Tracks a `mms_move_forward` over the given number of cells
+ */
+MmsStatus mms_pose_move_forward(struct PoseHandle *tracker, uint32_t distance);
 
-void clear_all_color(void);
+/**
+ * Creates a solver that treats unknown walls according to the `Assumption`. Returns null if it
+ * is out of range.
+ */
+struct SolverHandle *mms_solver_new(int32_t assumption);
 
-void set_text(uint32_t x, uint32_t y, const uint8_t *text_utf8, int32_t text_len);
+void mms_solver_free(struct SolverHandle *solver);
 
-void clear_text(uint32_t x, uint32_t y);
+/**
+ * Writes the `Assumption` the solver was created with into `assumption`
+ */
+MmsStatus mms_solver_assumption(struct SolverHandle *solver, Assumption *assumption);
 
-void clear_all_text(void);
+/**
+ * Computes the distance of every cell to the nearest target. Without targets, i.e. null or a
+ * length of `0`, the goal cells are used.
+ */
+MmsStatus mms_solver_flood(struct SolverHandle *solver,
+                           struct MazeHandle *maze,
+                           const struct Cell *targets,
+                           uint32_t targets_len);
 
-bool was_reset(void);
+/**
+ * The distance of the cell to the nearest target of the last `mms_solver_flood`, or `-1` if it
+ * cannot reach any target or nothing was flooded yet
+ */
+MmsStatus mms_solver_distance(struct SolverHandle *solver, struct Cell cell, int64_t *distance);
 
-void ack_reset(void);
+/**
+ * The direction of the next step from the pose towards the targets of the last
+ * `mms_solver_flood`. `has_step` is `false` at a target or if no target can be reached.
+ */
+MmsStatus mms_solver_next_step(struct SolverHandle *solver,
+                               struct MazeHandle *maze,
+                               struct MmsPose pose,
+                               Direction *direction,
+                               bool *has_step);
 
-void free_byte_buffer(struct ByteBuffer *buffer);
+/**
+ * Plans the shortest path from the pose to the nearest target, including the start and the final
+ * cell. Without targets, i.e. null or a length of `0`, the goal cells are used.
+ *
+ * Writes up to `capacity` cells into `path` and the full length into `path_len`, which is `0` if
+ * no target can be reached. Call it again with a larger array if `path_len` exceeds `capacity`.
+ */
+MmsStatus mms_solver_plan_path(struct SolverHandle *solver,
+                               struct MazeHandle *maze,
+                               struct MmsPose pose,
+                               const struct Cell *targets,
+                               uint32_t targets_len,
+                               struct Cell *path,
+                               uint32_t capacity,
+                               uint32_t *path_len);
 
-struct ByteBuffer *get_stat(const uint8_t *query_utf8, int32_t query_len);
+/**
+ * Plans the shortest path like `mms_solver_plan_path`, but writes it into a new buffer, which is
+ * empty if no target can be reached. Free it with `mms_free_cell_buffer`.
+ */
+MmsStatus mms_solver_path(struct SolverHandle *solver,
+                          struct MazeHandle *maze,
+                          struct MmsPose pose,
+                          const struct Cell *targets,
+                          uint32_t targets_len,
+                          struct CellBuffer **path);
@@ -0,0 +1,304 @@
+// A header-only C++17 wrapper over the generated bindings.hpp.
+//
+// `mms::Mouse` owns an `MmsHandle`, throws `mms::Error` for every failed call and frees returned
+// buffers itself. The raw C api stays available through `Mouse::handle`.
+
+#pragma once
+
+#include <cstdint>
+#include <limits>
+#include <memory>
+#include <optional>
+#include <stdexcept>
+#include <string>
+#include <string_view>
+#include <utility>
+#include <vector>
+
+#include "bindings.hpp"
+
+namespace mms {
+
+/// The direction for a wall
+enum class Direction : int32_t {
+  North,
+  East,
+  South,
+  West,
+};
+
+/// The cell color
+enum class CellColor : int32_t {
+  Black,
+  Blue,
+  Gray,
+  Cyan,
+  Green,
+  Orange,
+  Red,
+  White,
+  Yellow,
+  DarkBlue,
+  DarkCyan,
+  DarkGray,
+  DarkGreen,
+  DarkRed,
+  DarkYellow,
+};
+
+/// Which stat to query
+enum class StatQuery : int32_t {
+  TotalDistance,
+  TotalTurns,
+  BestRunDistance,
+  BestRunTurns,
+  CurrentRunDistance,
+  CurrentRunTurns,
+  TotalEffectiveDistance,
+  BestRunEffectiveDistance,
+  CurrentRunEffectiveDistance,
+  Score,
+};
+
+/// Why a call failed, see `MmsStatus` in bindings.hpp
+enum class Status : int32_t {
+  Ok,
+  ParseIntError,
+  ParseFloatError,
+  ParseStatQueryError,
+  IoError,
+  InvalidAck,
+  InvalidColorString,
+  InvalidDirectionString,
+  InvalidMazeFile,
+  InvalidArgument,
+  Panic,
+  InvalidMazeSize,
+};
+
+static_assert(static_cast<int32_t>(Direction::West) ==
+              static_cast<int32_t>(::Direction::Direction_West));
+static_assert(static_cast<int32_t>(CellColor::DarkYellow) ==
+              static_cast<int32_t>(::CellColor::CellColor_DarkYellow));
+static_assert(static_cast<int32_t>(StatQuery::Score) ==
+              static_cast<int32_t>(::StatQuery::StatQuery_Score));
+static_assert(static_cast<int32_t>(Status::InvalidMazeSize) ==
+              static_cast<int32_t>(::MmsStatus::MmsStatus_InvalidMazeSize));
+
+/// A failed call, with the status and the message of `mms_last_error_message`
+class Error : public std::runtime_error {
+ public:
+  Error(Status status, const std::string &message)
+      : std::runtime_error(message), status_(status) {}
+
+  Status status() const noexcept { return status_; }
+
+ private:
+  Status status_;
+};
+
+/// The value of a single stat
+struct Stat {
+  StatQuery query;
+  /// `true` for effective distances and the score, `false` for stats counted in cells and turns
+  bool is_float;
+  /// Empty while mms has no value yet, e.g. for the best run before the first run finished
+  std::optional<float> value;
+};
+
+namespace detail {
+
+struct HandleDeleter {
+  void operator()(MmsHandle *handle) const noexcept { mms_handle_free(handle); }
+};
+
+struct ByteBufferDeleter {
+  void operator()(ByteBuffer *buffer) const noexcept { mms_free_byte_buffer(buffer); }
+};
+
+struct StatValueBufferDeleter {
+  void operator()(StatValueBuffer *buffer) const noexcept { mms_free_stat_value_buffer(buffer); }
+};
+
+inline std::string to_string(ByteBuffer *buffer) {
+  std::unique_ptr<ByteBuffer, ByteBufferDeleter> owned(buffer);
+  if (!owned) {
+    return {};
+  }
+  return std::string(reinterpret_cast<const char *>(owned->ptr),
+                     static_cast<size_t>(owned->length));
+}
+
+inline void check(MmsStatus status) {
+  if (status != MmsStatus::MmsStatus_Ok) {
+    throw Error(static_cast<Status>(status), to_string(mms_last_error_message()));
+  }
+}
+
+// Null callbacks are reported as `InvalidArgument` instead of a failed handle creation
+inline MmsHandle *new_callbacks_handle(ReadLineCallback read_line, WriteLineCallback write_line,
+                                       void *user_data) {
+  MmsHandle *handle = mms_handle_new_callbacks(read_line, write_line, user_data);
+  if (!handle && (!read_line || !write_line)) {
+    throw Error(Status::InvalidArgument, to_string(mms_last_error_message()));
+  }
+  return handle;
+}
+
+inline Stat to_stat(const StatValue &value) {
+  Stat stat{static_cast<StatQuery>(value.query), value.kind == StatKind::StatKind_Float,
+            std::nullopt};
+  if (value.has_value) {
+    stat.value = value.float_value;
+  }
+  return stat;
+}
+
+}  // namespace detail
+
+/// A connection to mms. Moving a mouse leaves the source without a connection, every call on it
+/// throws an `Error` with `Status::InvalidArgument`.
+class Mouse {
+ public:
+  /// Talks to mms over `stdin` and `stdout`
+  Mouse() : Mouse(mms_handle_new_stdio()) {}
+
+  /// Talks to mms through the callbacks, see `mms_handle_new_callbacks`. Throws an `Error` with
+  /// `Status::InvalidArgument` if a callback is null.
+  Mouse(ReadLineCallback read_line, WriteLineCallback write_line, void *user_data)
+      : Mouse(detail::new_callbacks_handle(read_line, write_line, user_data)) {}
+
+  Mouse(const Mouse &) = delete;
+  Mouse &operator=(const Mouse &) = delete;
+  Mouse(Mouse &&) noexcept = default;
+  Mouse &operator=(Mouse &&) noexcept = default;
+  ~Mouse() = default;
+
+  /// The underlying handle, for the functions of bindings.hpp. It stays owned by the mouse.
+  MmsHandle *handle() const noexcept { return handle_.get(); }
+
+  int32_t maze_width() {
+    int32_t width = 0;
+    detail::check(::mms_get_maze_width(handle(), &width));
+    return width;
+  }
+
+  int32_t maze_height() {
+    int32_t height = 0;
+    detail::check(::mms_get_maze_height(handle(), &height));
+    return height;
+  }
+
+  bool wall_front() {
+    bool wall = false;
+    detail::check(::mms_wall_front(handle(), &wall));
+    return wall;
+  }
+
+  bool wall_right() {
+    bool wall = false;
+    detail::check(::mms_wall_right(handle(), &wall));
+    return wall;
+  }
+
+  bool wall_left() {
+    bool wall = false;
+    detail::check(::mms_wall_left(handle(), &wall));
+    return wall;
+  }
+
+  /// Throws an `Error` with `Status::InvalidArgument` for a distance of `0`, which the C api
+  /// would treat as one cell
+  void move_forward(uint32_t distance = 1) {
+    if (distance == 0) {
+      throw Error(Status::InvalidArgument, "InvalidArgument: the distance has to be at least 1");
+    }
+    detail::check(::mms_move_forward(handle(), distance));
+  }
+
+  void turn_right() { detail::check(::mms_turn_right(handle())); }
+
+  void turn_left() { detail::check(::mms_turn_left(handle())); }
+
+  void set_wall(uint32_t x, uint32_t y, Direction direction) {
+    detail::check(mms_set_wall(handle(), x, y, static_cast<int32_t>(direction)));
+  }
+
+  void clear_wall(uint32_t x, uint32_t y, Direction direction) {
+    detail::check(mms_clear_wall(handle(), x, y, static_cast<int32_t>(direction)));
+  }
+
+  void set_color(uint32_t x, uint32_t y, CellColor color) {
+    detail::check(mms_set_color(handle(), x, y, static_cast<int32_t>(color)));
+  }
+
+  void clear_color(uint32_t x, uint32_t y) { detail::check(::mms_clear_color(handle(), x, y)); }
+
+  void clear_all_color() { detail::check(::mms_clear_all_color(handle())); }
+
+  void set_text(uint32_t x, uint32_t y, std::string_view text) {
+    if (text.size() > static_cast<size_t>(std::numeric_limits<int32_t>::max())) {
+      throw Error(Status::InvalidArgument, "InvalidArgument: text is too long");
+    }
+    detail::check(::mms_set_text(handle(), x, y, reinterpret_cast<const uint8_t *>(text.data()),
+                             static_cast<int32_t>(text.size())));
+  }
+
+  void clear_text(uint32_t x, uint32_t y) { detail::check(::mms_clear_text(handle(), x, y)); }
+
+  void clear_all_text() { detail::check(::mms_clear_all_text(handle())); }
+
+  bool was_reset() {
+    bool reset = false;
+    detail::check(::mms_was_reset(handle(), &reset));
+    return reset;
+  }
+
+  void ack_reset() { detail::check(::mms_ack_reset(handle())); }
+
+  /// The stat as mms sent it
+  std::string stat_text(StatQuery query) {
+    ByteBuffer *value = nullptr;
+    detail::check(mms_get_stat_text(handle(), static_cast<int32_t>(query), &value));
+    return detail::to_string(value);
+  }
+
+  Stat stat(StatQuery query) {
+    StatValue value{};
+    detail::check(mms_get_stat(handle(), static_cast<int32_t>(query), &value));
+    return detail::to_stat(value);
+  }
+
+  /// The given stats in the same order, or all of them without queries
+  std::vector<Stat> stats(const std::vector<StatQuery> &queries = {}) {
+    if (queries.size() > static_cast<size_t>(std::numeric_limits<int32_t>::max())) {
+      throw Error(Status::InvalidArgument, "InvalidArgument: too many queries");
+    }
+    std::vector<int32_t> native;
+    native.reserve(queries.size());
+    for (StatQuery query : queries) {
+      native.push_back(static_cast<int32_t>(query));
+    }
+    StatValueBuffer *values = nullptr;
+    detail::check(mms_get_stats(handle(), native.data(), static_cast<int32_t>(native.size()),
+                                &values));
+    std::unique_ptr<StatValueBuffer, detail::StatValueBufferDeleter> owned(values);
+    std::vector<Stat> stats;
+    stats.reserve(static_cast<size_t>(owned->length));
+    for (int32_t i = 0; i < owned->length; ++i) {
+      stats.push_back(detail::to_stat(owned->ptr[i]));
+    }
+    return stats;
+  }
+
+ private:
+  explicit Mouse(MmsHandle *handle) : handle_(handle) {
+    if (!handle_) {
+      throw Error(Status::Panic, "Panic: creating the mms handle failed");
+    }
+  }
+
+  std::unique_ptr<MmsHandle, detail::HandleDeleter> handle_;
+};
+
+}  // namespace mms
@@ -9,85 +9,385 @@ using System.Runtime.InteropServices;
 
 namespace mms_sharp
 {
-    internal static unsafe partial class MmsApi
+    public static unsafe partial class MmsApi
     {
         const string __DllName = "MmsSharp";
 
-        [DllImport(__DllName, EntryPoint = "maze_width", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern int maze_width();
+        /// <summary>The message of the last failed call on this thread, or null if no call failed yet. Free it with `mms_free_byte_buffer`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_last_error_message", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern ByteBuffer* mms_last_error_message();
 
-        [DllImport(__DllName, EntryPoint = "maze_height", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern int maze_height();
+        /// <summary>Creates a handle that talks to mms over `stdin` and `stdout`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_handle_new_stdio", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsHandle* mms_handle_new_stdio();
 
-        [DllImport(__DllName, EntryPoint = "wall_front", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        [return: MarshalAs(UnmanagedType.U1)]
-        public static extern bool wall_front();
+        /// <summary>Creates a handle that talks to mms through the callbacks. `user_data` is passed to every call and has to stay valid until the handle is freed. Returns null if a callback is null.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_handle_new_callbacks", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsHandle* mms_handle_new_callbacks(delegate* unmanaged[Cdecl]<void*, byte*, int, int> read_line, delegate* unmanaged[Cdecl]<void*, byte*, int, int> write_line, void* user_data);
 
-        [DllImport(__DllName, EntryPoint = "wall_right", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        [return: MarshalAs(UnmanagedType.U1)]
-        public static extern bool wall_right();
+        /// <summary>Frees a handle created by `mms_handle_new_stdio` or `mms_handle_new_callbacks`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_handle_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_handle_free(MmsHandle* handle);
 
-        [DllImport(__DllName, EntryPoint = "wall_left", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        [return: MarshalAs(UnmanagedType.U1)]
-        public static extern bool wall_left();
+        [DllImport(__DllName, EntryPoint = "mms_get_maze_width", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_maze_width(MmsHandle* handle, int* width);
 
-        [DllImport(__DllName, EntryPoint = "move_forward", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void move_forward(uint distance);
+        [DllImport(__DllName, EntryPoint = "mms_get_maze_height", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_maze_height(MmsHandle* handle, int* height);
 
-        [DllImport(__DllName, EntryPoint = "turn_right", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void turn_right();
+        [DllImport(__DllName, EntryPoint = "mms_wall_front", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_wall_front(MmsHandle* handle, bool* wall);
 
-        [DllImport(__DllName, EntryPoint = "turn_left", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void turn_left();
+        [DllImport(__DllName, EntryPoint = "mms_wall_right", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_wall_right(MmsHandle* handle, bool* wall);
 
+        [DllImport(__DllName, EntryPoint = "mms_wall_left", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_wall_left(MmsHandle* handle, bool* wall);
+
+        [DllImport(__DllName, EntryPoint = "mms_move_forward", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_move_forward(MmsHandle* handle, uint distance);
+
+        [DllImport(__DllName, EntryPoint = "mms_turn_right", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_turn_right(MmsHandle* handle);
+
+        [DllImport(__DllName, EntryPoint = "mms_turn_left", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_turn_left(MmsHandle* handle);
+
+        /// <summary>`direction` is a `Direction`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_set_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_set_wall(MmsHandle* handle, uint x, uint y, int direction);
+
+        /// <summary>Deprecated: use `mms_set_wall`, which takes a `Direction` instead of a string</summary>
         [DllImport(__DllName, EntryPoint = "set_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void set_wall(uint x, uint y, byte* direction_utf8, int direction_len);
+        public static extern MmsStatus set_wall(MmsHandle* handle, uint x, uint y, byte* direction_utf8, int direction_len);
+
+        /// <summary>`direction` is a `Direction`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_clear_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_clear_wall(MmsHandle* handle, uint x, uint y, int direction);
 
+        /// <summary>Deprecated: use `mms_clear_wall`, which takes a `Direction` instead of a string</summary>
         [DllImport(__DllName, EntryPoint = "clear_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void clear_wall(uint x, uint y, byte* direction_utf8, int direction_len);
+        public static extern MmsStatus clear_wall(MmsHandle* handle, uint x, uint y, byte* direction_utf8, int direction_len);
+
+        /// <summary>`color` is a `CellColor`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_set_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_set_color(MmsHandle* handle, uint x, uint y, int color);
 
+        /// <summary>Deprecated: use `mms_set_color`, which takes a `CellColor` instead of a string</summary>
         [DllImport(__DllName, EntryPoint = "set_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void set_color(uint x, uint y, byte* color_utf8, int color_len);
+        public static extern MmsStatus set_color(MmsHandle* handle, uint x, uint y, byte* color_utf8, int color_len);
 
-        [DllImport(__DllName, EntryPoint = "clear_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void clear_color(uint x, uint y);
+        /// <summary>Parses the character mms uses for a color, e.g. `G` for `DarkGreen`, into `color`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_parse_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_parse_color(byte* color_utf8, int color_len, CellColor* color);
 
-        [DllImport(__DllName, EntryPoint = "clear_all_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void clear_all_color();
+        [DllImport(__DllName, EntryPoint = "mms_clear_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_clear_color(MmsHandle* handle, uint x, uint y);
 
-        [DllImport(__DllName, EntryPoint = "set_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void set_text(uint x, uint y, byte* text_utf8, int text_len);
+        [DllImport(__DllName, EntryPoint = "mms_clear_all_color", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_clear_all_color(MmsHandle* handle);
 
-        [DllImport(__DllName, EntryPoint = "clear_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void clear_text(uint x, uint y);
+        [DllImport(__DllName, EntryPoint = "mms_set_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_set_text(MmsHandle* handle, uint x, uint y, byte* text_utf8, int text_len);
 
-        [DllImport(__DllName, EntryPoint = "clear_all_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void clear_all_text();
+        /// <summary>Like `mms_set_text`, but takes a NUL-terminated string</summary>
+        [DllImport(__DllName, EntryPoint = "mms_set_text_cstr", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_set_text_cstr(MmsHandle* handle, uint x, uint y, byte* text);
 
-        [DllImport(__DllName, EntryPoint = "was_reset", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        [return: MarshalAs(UnmanagedType.U1)]
-        public static extern bool was_reset();
+        [DllImport(__DllName, EntryPoint = "mms_clear_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_clear_text(MmsHandle* handle, uint x, uint y);
 
-        [DllImport(__DllName, EntryPoint = "ack_reset", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void ack_reset();
+        [DllImport(__DllName, EntryPoint = "mms_clear_all_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_clear_all_text(MmsHandle* handle);
 
-        [DllImport(__DllName, EntryPoint = "free_byte_buffer", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern void free_byte_buffer(ByteBuffer* buffer);
+        [DllImport(__DllName, EntryPoint = "mms_was_reset", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_was_reset(MmsHandle* handle, bool* reset);
 
+        [DllImport(__DllName, EntryPoint = "mms_ack_reset", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_ack_reset(MmsHandle* handle);
+
+        [DllImport(__DllName, EntryPoint = "mms_free_byte_buffer", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_free_byte_buffer(ByteBuffer* buffer);
+
+        [DllImport(__DllName, EntryPoint = "mms_free_cell_buffer", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_free_cell_buffer(CellBuffer* buffer);
+
+        [DllImport(__DllName, EntryPoint = "mms_free_stat_value_buffer", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_free_stat_value_buffer(StatValueBuffer* buffer);
+
+        /// <summary>Writes the stat, `query` is a `StatQuery`, as a string into `value`. Free it with `mms_free_byte_buffer`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_get_stat_text", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_stat_text(MmsHandle* handle, int query, ByteBuffer** value);
+
+        /// <summary>Deprecated: use `mms_get_stat_text`, which takes a `StatQuery` instead of a string</summary>
         [DllImport(__DllName, EntryPoint = "get_stat", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
-        public static extern ByteBuffer* get_stat(byte* query_utf8, int query_len);
+        public static extern MmsStatus get_stat(MmsHandle* handle, byte* query_utf8, int query_len, ByteBuffer** value);
+
+        /// <summary>Writes the value of the stat, `query` is a `StatQuery`, into `value`, without any string parsing or freeing</summary>
+        [DllImport(__DllName, EntryPoint = "mms_get_stat", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_stat(MmsHandle* handle, int query, StatValue* value);
+
+        /// <summary>Queries all stats and writes them into `stats`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_get_all_stats", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_all_stats(MmsHandle* handle, RunStats* stats);
+
+        /// <summary>Queries the given stats, or all of them without queries, i.e. null or a length of `0`, and writes them into `stats` in the same order. `queries` are `StatQuery` values. A negative `queries_len` fails with `InvalidArgument`. Free `stats` with `mms_free_stat_value_buffer`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_get_stats", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_get_stats(MmsHandle* handle, int* queries, int queries_len, StatValueBuffer** stats);
+
+        /// <summary>Creates an empty maze of the given size, with only the outer walls known</summary>
+        [DllImport(__DllName, EntryPoint = "mms_maze_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MazeHandle* mms_maze_new(uint width, uint height);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_maze_free(MazeHandle* maze);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_width", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_width(MazeHandle* maze, uint* width);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_height", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_height(MazeHandle* maze, uint* height);
+
+        /// <summary>Records a sensed wall on both of its sides</summary>
+        [DllImport(__DllName, EntryPoint = "mms_maze_record_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_record_wall(MazeHandle* maze, Cell cell, int direction, [MarshalAs(UnmanagedType.U1)] bool present);
+
+        /// <summary>Records the three sensor readings taken at the pose and marks its cell as visited</summary>
+        [DllImport(__DllName, EntryPoint = "mms_maze_record_sensors", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_record_sensors(MazeHandle* maze, MmsPose pose, [MarshalAs(UnmanagedType.U1)] bool front, [MarshalAs(UnmanagedType.U1)] bool left, [MarshalAs(UnmanagedType.U1)] bool right);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_wall", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_wall(MazeHandle* maze, Cell cell, int direction, WallState* wall);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_mark_visited", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_mark_visited(MazeHandle* maze, Cell cell);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_is_visited", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_is_visited(MazeHandle* maze, Cell cell, bool* visited);
+
+        /// <summary>Writes the goal cells in the center of the maze into `goals`. Free it with `mms_free_cell_buffer`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_maze_goals", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_goals(MazeHandle* maze, CellBuffer** goals);
+
+        [DllImport(__DllName, EntryPoint = "mms_maze_is_goal", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_maze_is_goal(MazeHandle* maze, Cell cell, bool* goal);
+
+        /// <summary>Creates a pose tracker at the start: `(0, 0)` facing north</summary>
+        [DllImport(__DllName, EntryPoint = "mms_pose_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern PoseHandle* mms_pose_new();
+
+        [DllImport(__DllName, EntryPoint = "mms_pose_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_pose_free(PoseHandle* tracker);
+
+        [DllImport(__DllName, EntryPoint = "mms_pose_get", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_pose_get(PoseHandle* tracker, MmsPose* pose);
+
+        [DllImport(__DllName, EntryPoint = "mms_pose_set", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_pose_set(PoseHandle* tracker, MmsPose pose);
+
+        [DllImport(__DllName, EntryPoint = "mms_pose_turn_left", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_pose_turn_left(PoseHandle* tracker);
+
+        [DllImport(__DllName, EntryPoint = "mms_pose_turn_right", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_pose_turn_right(PoseHandle* tracker);
+
+        /// <summary>Tracks a `mms_move_forward` over the given number of cells</summary>
+        [DllImport(__DllName, EntryPoint = "mms_pose_move_forward", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_pose_move_forward(PoseHandle* tracker, uint distance);
+
+        /// <summary>Creates a solver that treats unknown walls according to the `Assumption`. Returns null if it is out of range.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_new", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern SolverHandle* mms_solver_new(int assumption);
+
+        [DllImport(__DllName, EntryPoint = "mms_solver_free", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern void mms_solver_free(SolverHandle* solver);
+
+        /// <summary>Writes the `Assumption` the solver was created with into `assumption`</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_assumption", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_assumption(SolverHandle* solver, Assumption* assumption);
+
+        /// <summary>Computes the distance of every cell to the nearest target. Without targets, i.e. null or a length of `0`, the goal cells are used.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_flood", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_flood(SolverHandle* solver, MazeHandle* maze, Cell* targets, uint targets_len);
+
+        /// <summary>The distance of the cell to the nearest target of the last `mms_solver_flood`, or `-1` if it cannot reach any target or nothing was flooded yet</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_distance", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_distance(SolverHandle* solver, Cell cell, long* distance);
+
+        /// <summary>The direction of the next step from the pose towards the targets of the last `mms_solver_flood`. `has_step` is `false` at a target or if no target can be reached.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_next_step", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_next_step(SolverHandle* solver, MazeHandle* maze, MmsPose pose, Direction* direction, bool* has_step);
+
+        /// <summary>Plans the shortest path from the pose to the nearest target, including the start and the final cell. Without targets, i.e. null or a length of `0`, the goal cells are used.  Writes up to `capacity` cells into `path` and the full length into `path_len`, which is `0` if no target can be reached. Call it again with a larger array if `path_len` exceeds `capacity`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_plan_path", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_plan_path(SolverHandle* solver, MazeHandle* maze, MmsPose pose, Cell* targets, uint targets_len, Cell* path, uint capacity, uint* path_len);
+
+        /// <summary>Plans the shortest path like `mms_solver_plan_path`, but writes it into a new buffer, which is empty if no target can be reached. Free it with `mms_free_cell_buffer`.</summary>
+        [DllImport(__DllName, EntryPoint = "mms_solver_path", CallingConvention = CallingConvention.Cdecl, ExactSpelling = true)]
+        public static extern MmsStatus mms_solver_path(SolverHandle* solver, MazeHandle* maze, MmsPose pose, Cell* targets, uint targets_len, CellBuffer** path);
 
 
     }
 
     [StructLayout(LayoutKind.Sequential)]
-    internal unsafe partial struct ByteBuffer
+    public unsafe partial struct ByteBuffer
     {
         public byte* ptr;
         public int length;
         public int capacity;
     }
 
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct CellBuffer
+    {
+        public Cell* ptr;
+        public int length;
+        public int capacity;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct StatValueBuffer
+    {
+        public StatValue* ptr;
+        public int length;
+        public int capacity;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct StatValue
+    {
+        public StatQuery query;
+        public StatKind kind;
+        [MarshalAs(UnmanagedType.U1)] public bool has_value;
+        public int int_value;
+        public float float_value;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct RunStats
+    {
+        public StatValue total_distance;
+        public StatValue total_turns;
+        public StatValue best_run_distance;
+        public StatValue best_run_turns;
+        public StatValue current_run_distance;
+        public StatValue current_run_turns;
+        public StatValue total_effective_distance;
+        public StatValue best_run_effective_distance;
+        public StatValue current_run_effective_distance;
+        public StatValue score;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct MmsPose
+    {
+        public Cell cell;
+        public int heading;
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct MmsHandle
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct MazeHandle
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct PoseHandle
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct SolverHandle
+    {
+    }
+
+    [StructLayout(LayoutKind.Sequential)]
+    public unsafe partial struct Cell
+    {
+        public uint x;
+        public uint y;
+    }
+
+
+    public enum MmsStatus : int
+    {
+        Ok = 0,
+        ParseIntError,
+        ParseFloatError,
+        ParseStatQueryError,
+        IoError,
+        InvalidAck,
+        InvalidColorString,
+        InvalidDirectionString,
+        InvalidMazeFile,
+        InvalidArgument,
+        Panic,
+        InvalidMazeSize,
+    }
+
+    public enum StatKind : int
+    {
+        Int,
+        Float,
+    }
+
+    public enum StatQuery : int
+    {
+        TotalDistance,
+        TotalTurns,
+        BestRunDistance,
+        BestRunTurns,
+        CurrentRunDistance,
+        CurrentRunTurns,
+        TotalEffectiveDistance,
+        BestRunEffectiveDistance,
+        CurrentRunEffectiveDistance,
+        Score,
+    }
+
+    public enum Direction : int
+    {
+        North,
+        East,
+        South,
+        West,
+    }
+
+    public enum CellColor : int
+    {
+        Black,
+        Blue,
+        Gray,
+        Cyan,
+        Green,
+        Orange,
+        Red,
+        White,
+        Yellow,
+        DarkBlue,
+        DarkCyan,
+        DarkGray,
+        DarkGreen,
+        DarkRed,
+        DarkYellow,
+    }
+
+    public enum WallState : int
+    {
+        Unknown,
+        Open,
+        Wall,
+    }
+
+    public enum Assumption : int
+    {
+        Optimistic,
+        Pessimistic,
+    }
 
 
 }
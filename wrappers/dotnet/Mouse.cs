@@ -0,0 +1,207 @@
+using System.Text;
+using Native = mms_sharp;
+
+namespace MmsSharp
+{
+    /// <summary>A failed call, with the status and the message reported by the Rust side</summary>
+    public class MmsException : Exception
+    {
+        public MmsException(Native.MmsStatus status, string message) : base(message)
+        {
+            Status = status;
+        }
+
+        public Native.MmsStatus Status { get; }
+    }
+
+    /// <summary>The value of a single stat</summary>
+    /// <param name="Query">The stat</param>
+    /// <param name="IsFloat"><c>true</c> for effective distances and the score, <c>false</c> for stats counted in cells and turns</param>
+    /// <param name="Value"><c>null</c> while mms has no value yet, e.g. for the best run before the first run finished</param>
+    public readonly record struct Stat(Native.StatQuery Query, bool IsFloat, float? Value);
+
+    /// <summary>A connection to mms over <c>stdin</c> and <c>stdout</c>. Dispose it to free the native handle.</summary>
+    public sealed unsafe class Mouse : IDisposable
+    {
+        private Native.MmsHandle* handle;
+
+        public Mouse()
+        {
+            handle = Native.MmsApi.mms_handle_new_stdio();
+            if (handle == null)
+            {
+                throw new MmsException(Native.MmsStatus.Panic, "Panic: creating the mms handle failed");
+            }
+        }
+
+        ~Mouse() => Free();
+
+        public void Dispose()
+        {
+            Free();
+            GC.SuppressFinalize(this);
+        }
+
+        public int MazeWidth()
+        {
+            int width;
+            Check(Native.MmsApi.mms_get_maze_width(Handle, &width));
+            return width;
+        }
+
+        public int MazeHeight()
+        {
+            int height;
+            Check(Native.MmsApi.mms_get_maze_height(Handle, &height));
+            return height;
+        }
+
+        public bool WallFront()
+        {
+            bool wall;
+            Check(Native.MmsApi.mms_wall_front(Handle, &wall));
+            return wall;
+        }
+
+        public bool WallRight()
+        {
+            bool wall;
+            Check(Native.MmsApi.mms_wall_right(Handle, &wall));
+            return wall;
+        }
+
+        public bool WallLeft()
+        {
+            bool wall;
+            Check(Native.MmsApi.mms_wall_left(Handle, &wall));
+            return wall;
+        }
+
+        /// <summary>Throws an <c>MmsException</c> with <c>InvalidArgument</c> for a distance of <c>0</c>, which the C api would treat as one cell</summary>
+        public void MoveForward(uint distance = 1)
+        {
+            if (distance == 0)
+            {
+                throw new MmsException(Native.MmsStatus.InvalidArgument, "InvalidArgument: the distance has to be at least 1");
+            }
+            Check(Native.MmsApi.mms_move_forward(Handle, distance));
+        }
+
+        public void TurnRight() => Check(Native.MmsApi.mms_turn_right(Handle));
+
+        public void TurnLeft() => Check(Native.MmsApi.mms_turn_left(Handle));
+
+        public void SetWall(uint x, uint y, Native.Direction direction) =>
+            Check(Native.MmsApi.mms_set_wall(Handle, x, y, (int)direction));
+
+        public void ClearWall(uint x, uint y, Native.Direction direction) =>
+            Check(Native.MmsApi.mms_clear_wall(Handle, x, y, (int)direction));
+
+        public void SetColor(uint x, uint y, Native.CellColor color) =>
+            Check(Native.MmsApi.mms_set_color(Handle, x, y, (int)color));
+
+        public void ClearColor(uint x, uint y) => Check(Native.MmsApi.mms_clear_color(Handle, x, y));
+
+        public void ClearAllColor() => Check(Native.MmsApi.mms_clear_all_color(Handle));
+
+        public void SetText(uint x, uint y, string text)
+        {
+            var bytes = Encoding.UTF8.GetBytes(text);
+            fixed (byte* ptr = bytes)
+            {
+                Check(Native.MmsApi.mms_set_text(Handle, x, y, ptr, bytes.Length));
+            }
+        }
+
+        public void ClearText(uint x, uint y) => Check(Native.MmsApi.mms_clear_text(Handle, x, y));
+
+        public void ClearAllText() => Check(Native.MmsApi.mms_clear_all_text(Handle));
+
+        public bool WasReset()
+        {
+            bool reset;
+            Check(Native.MmsApi.mms_was_reset(Handle, &reset));
+            return reset;
+        }
+
+        public void AckReset() => Check(Native.MmsApi.mms_ack_reset(Handle));
+
+        /// <summary>The stat as mms sent it</summary>
+        public string GetStatText(Native.StatQuery query)
+        {
+            Native.ByteBuffer* value;
+            Check(Native.MmsApi.mms_get_stat_text(Handle, (int)query, &value));
+            return TakeString(value) ?? "";
+        }
+
+        public Stat GetStat(Native.StatQuery query)
+        {
+            Native.StatValue value;
+            Check(Native.MmsApi.mms_get_stat(Handle, (int)query, &value));
+            return ToStat(value);
+        }
+
+        /// <summary>The given stats in the same order, or all of them without queries</summary>
+        public Stat[] GetStats(params Native.StatQuery[] queries)
+        {
+            var native = Array.ConvertAll(queries, query => (int)query);
+            Native.StatValueBuffer* values;
+            fixed (int* ptr = native)
+            {
+                Check(Native.MmsApi.mms_get_stats(Handle, ptr, native.Length, &values));
+            }
+            try
+            {
+                var stats = new Stat[values->length];
+                for (var i = 0; i < stats.Length; i++)
+                {
+                    stats[i] = ToStat(values->ptr[i]);
+                }
+                return stats;
+            }
+            finally
+            {
+                Native.MmsApi.mms_free_stat_value_buffer(values);
+            }
+        }
+
+        private Native.MmsHandle* Handle =>
+            handle != null ? handle : throw new ObjectDisposedException(nameof(Mouse));
+
+        private void Free()
+        {
+            if (handle != null)
+            {
+                Native.MmsApi.mms_handle_free(handle);
+                handle = null;
+            }
+        }
+
+        private static void Check(Native.MmsStatus status)
+        {
+            if (status != Native.MmsStatus.Ok)
+            {
+                throw new MmsException(status, TakeString(Native.MmsApi.mms_last_error_message()) ?? status.ToString());
+            }
+        }
+
+        private static string? TakeString(Native.ByteBuffer* buffer)
+        {
+            if (buffer == null)
+            {
+                return null;
+            }
+            try
+            {
+                return Encoding.UTF8.GetString(buffer->ptr, buffer->length);
+            }
+            finally
+            {
+                Native.MmsApi.mms_free_byte_buffer(buffer);
+            }
+        }
+
+        private static Stat ToStat(Native.StatValue value) =>
+            new(value.query, value.kind == Native.StatKind.Float, value.has_value ? value.float_value : (float?)null);
+    }
+}